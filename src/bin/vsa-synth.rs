@@ -0,0 +1,253 @@
+use std::io::Read;
+use std::sync::Arc;
+use std::time::Duration;
+
+use interactive_vsa::synth::{self, vsa::Cost};
+use interactive_vsa::{distinguishing_input, Lit, Ranking, StringDsl, SynthConfig, Task, VSA};
+
+const USAGE: &str = "\
+usage: vsa-synth [OPTIONS] [FILE]
+
+Reads input/output examples from FILE (or stdin), one per line,
+with the input and output separated by a tab, and prints the best
+program found by top down synthesis. An input with several columns
+has them separated by tabs too, the output is the last field. FILE
+can also be a .json or .csv task file. How many programs are left
+after each example is printed to stderr.
+
+options:
+    -c, --config FILE    read the synthesis config from a JSON file
+    -d, --distinguish    suggest an input the best programs disagree on,
+                         from the unlabeled inputs or made up
+    -k, --top-k N        also print the N best programs that fit every
+                         example, with their outputs on unlabeled inputs
+    -t, --timeout SECS   stop searching after SECS seconds
+    -v, --vsa            print the flattened VSA
+    -w, --weights FILE   pick programs with a ranking model from a JSON
+                         file, like the ones vsa-train writes
+    -h, --help           print this message";
+
+struct Args {
+    path: Option<String>,
+    config: Option<String>,
+    weights: Option<String>,
+    timeout: Option<Duration>,
+    top_k: Option<usize>,
+    distinguish: bool,
+    print_vsa: bool,
+}
+
+fn parse_args() -> Result<Args, String> {
+    let mut args = Args {
+        path: None,
+        config: None,
+        weights: None,
+        timeout: None,
+        top_k: None,
+        distinguish: false,
+        print_vsa: false,
+    };
+
+    let mut argv = std::env::args().skip(1);
+    while let Some(arg) = argv.next() {
+        match arg.as_str() {
+            "-c" | "--config" => {
+                args.config = Some(argv.next().ok_or("missing value for --config")?);
+            }
+            "-w" | "--weights" => {
+                args.weights = Some(argv.next().ok_or("missing value for --weights")?);
+            }
+            "-k" | "--top-k" => {
+                let k = argv.next().ok_or("missing value for --top-k")?;
+                let k = k
+                    .parse()
+                    .map_err(|_| format!("invalid value for --top-k: {k}"))?;
+                args.top_k = Some(k);
+            }
+            "-t" | "--timeout" => {
+                let t = argv.next().ok_or("missing value for --timeout")?;
+                let t = t
+                    .parse()
+                    .ok()
+                    .and_then(|secs| Duration::try_from_secs_f64(secs).ok())
+                    .ok_or_else(|| format!("invalid value for --timeout: {t}"))?;
+                args.timeout = Some(t);
+            }
+            "-d" | "--distinguish" => args.distinguish = true,
+            "-v" | "--vsa" => args.print_vsa = true,
+            "-h" | "--help" => {
+                println!("{USAGE}");
+                std::process::exit(0);
+            }
+            _ if arg.starts_with('-') && arg != "-" => return Err(format!("unknown option {arg}")),
+            _ if args.path.is_none() => args.path = Some(arg),
+            _ => return Err(format!("unexpected argument {arg}")),
+        }
+    }
+
+    Ok(args)
+}
+
+fn parse_examples(src: &str) -> Result<Vec<(Lit, Lit)>, String> {
+    src.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| match line.rsplit_once('\t') {
+            Some((inp, out)) => Ok((columns(inp), Lit::StringConst(out.to_string()))),
+            None => Err(format!("line {}: expected <input>\\t<output>", i + 1)),
+        })
+        .collect()
+}
+
+fn columns(inp: &str) -> Lit {
+    if inp.contains('\t') {
+        Lit::Tuple(
+            inp.split('\t')
+                .map(|col| Lit::StringConst(col.to_string()))
+                .collect(),
+        )
+    } else {
+        Lit::StringConst(inp.to_string())
+    }
+}
+
+fn read_examples(path: Option<&str>) -> Result<Vec<(Lit, Lit)>, String> {
+    let src = match path {
+        Some(path) if path != "-" => std::fs::read_to_string(path),
+        _ => {
+            let mut buf = String::new();
+            std::io::stdin().read_to_string(&mut buf).map(|_| buf)
+        }
+    }
+    .map_err(|e| format!("could not read examples: {e}"))?;
+
+    let examples = parse_examples(&src)?;
+    if examples.is_empty() {
+        Err("no examples given".to_string())
+    } else {
+        Ok(examples)
+    }
+}
+
+fn main() {
+    let args = parse_args().unwrap_or_else(|e| {
+        eprintln!("error: {e}\n\n{USAGE}");
+        std::process::exit(2);
+    });
+
+    let mut task = match args.path.as_deref() {
+        Some(path) if path.ends_with(".json") || path.ends_with(".csv") => {
+            Task::load(path).map_err(|e| e.to_string())
+        }
+        path => read_examples(path).map(Task::new),
+    }
+    .unwrap_or_else(|e| {
+        eprintln!("error: {e}");
+        std::process::exit(2);
+    });
+
+    if let Some(path) = &args.config {
+        task.config = SynthConfig::load(path).unwrap_or_else(|e| {
+            eprintln!("error: {e}");
+            std::process::exit(2);
+        });
+    }
+
+    if let Some(path) = &args.weights {
+        task.config.ranking = Some(Ranking::load(path).unwrap_or_else(|e| {
+            eprintln!("error: {e}");
+            std::process::exit(2);
+        }));
+    }
+
+    if let Some(timeout) = args.timeout {
+        task.config.timeout = Some(timeout);
+    }
+
+    // programs refer to the columns as X, X1, X2, ...
+    for (i, name) in task.columns.iter().enumerate() {
+        eprintln!("{} = {name}", Lit::Input(i));
+    }
+
+    let budget = task.config.budget();
+    let num_examples = task.examples.len();
+    let (vsa, ast, exhausted) = synth::top_down_observed(
+        &task.examples,
+        &task.config,
+        &budget,
+        |size, examples, vsa| {
+            eprintln!(
+                "size {size}, {examples}/{num_examples} examples: {}",
+                vsa.stats()
+            );
+        },
+    );
+    if let Some(reason) = exhausted {
+        eprintln!("stopped early: {reason}");
+    }
+    let vsa = Arc::new(vsa);
+    let flat_vsa = VSA::flatten(vsa.clone());
+
+    if args.print_vsa {
+        println!("{:?}", flat_vsa);
+    }
+
+    if let Some(k) = args.top_k {
+        let fits = |ast: &synth::AST| task.examples.iter().all(|(inp, out)| ast.eval(inp) == *out);
+        let mut best = match &task.config.ranking {
+            // the model only reorders the cheapest candidates
+            Some(ranking) => {
+                let mut scored = VSA::ranked(&vsa)
+                    .take(ranking.candidates.max(k))
+                    .filter(fits)
+                    .map(|ast| (ranking.score::<StringDsl>(&ast, &task.examples), ast))
+                    .collect::<Vec<_>>();
+                scored.sort_by(|(l, _), (r, _)| l.total_cmp(r));
+                scored
+                    .into_iter()
+                    .map(|(score, ast)| (format!("score {score:.2}"), ast))
+                    .collect::<Vec<_>>()
+            }
+            None => VSA::ranked(&vsa)
+                .filter(fits)
+                .take(k)
+                .map(|ast| (format!("cost {}", ast.cost()), ast))
+                .collect(),
+        };
+        best.truncate(k);
+        for (i, (rank, ast)) in best.into_iter().enumerate() {
+            println!("{}. [{rank}] {}", i + 1, ast);
+            for inp in &task.other_inputs {
+                println!("       {} -> {}", inp, ast.eval(inp));
+            }
+        }
+    }
+
+    if args.distinguish {
+        let example_inputs = task
+            .examples
+            .iter()
+            .map(|(inp, _)| inp.clone())
+            .collect::<Vec<_>>();
+        match distinguishing_input::<StringDsl>(&vsa, &example_inputs, &task.other_inputs) {
+            Some(question) => {
+                println!("what should {} give?", question.input);
+                for answer in &question.answers {
+                    println!(
+                        "    {} [cost {}] {}",
+                        answer.output, answer.cost, answer.program
+                    );
+                }
+            }
+            None => println!("no input found that the programs disagree on"),
+        }
+    }
+
+    match ast {
+        Some(ast) => println!("{}", ast),
+        None => {
+            eprintln!("no program found");
+            std::process::exit(1);
+        }
+    }
+}
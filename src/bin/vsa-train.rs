@@ -0,0 +1,124 @@
+use interactive_vsa::{Ranking, StringDsl, SynthConfig, Task, Training};
+
+const USAGE: &str = "\
+usage: vsa-train [OPTIONS] TASK...
+
+Fits the weights of a ranking model to a corpus of solved .json or
+.csv task files, so that the programs learned from the first example
+of each task that also get its other examples right rank first. How
+many tasks are ranked right after each epoch is printed to stderr,
+and the weights to stdout or the output file, for vsa-synth -w.
+
+options:
+    -c, --config FILE    read the synthesis config from a JSON file
+    -e, --epochs N       passes over the tasks (default 20)
+    -r, --rate RATE      learning rate (default 0.1)
+    -w, --weights FILE   start from these weights instead of the default
+    -o, --output FILE    write the weights to FILE
+    -h, --help           print this message";
+
+struct Args {
+    tasks: Vec<String>,
+    config: Option<String>,
+    weights: Option<String>,
+    output: Option<String>,
+    training: Training,
+}
+
+fn parse_args() -> Result<Args, String> {
+    let mut args = Args {
+        tasks: Vec::new(),
+        config: None,
+        weights: None,
+        output: None,
+        training: Training::default(),
+    };
+
+    let mut argv = std::env::args().skip(1);
+    while let Some(arg) = argv.next() {
+        match arg.as_str() {
+            "-c" | "--config" => {
+                args.config = Some(argv.next().ok_or("missing value for --config")?);
+            }
+            "-w" | "--weights" => {
+                args.weights = Some(argv.next().ok_or("missing value for --weights")?);
+            }
+            "-o" | "--output" => {
+                args.output = Some(argv.next().ok_or("missing value for --output")?);
+            }
+            "-e" | "--epochs" => {
+                let e = argv.next().ok_or("missing value for --epochs")?;
+                args.training.epochs = e
+                    .parse()
+                    .map_err(|_| format!("invalid value for --epochs: {e}"))?;
+            }
+            "-r" | "--rate" => {
+                let r = argv.next().ok_or("missing value for --rate")?;
+                args.training.learning_rate = r
+                    .parse()
+                    .ok()
+                    .filter(|rate: &f64| *rate > 0.0)
+                    .ok_or_else(|| format!("invalid value for --rate: {r}"))?;
+            }
+            "-h" | "--help" => {
+                println!("{USAGE}");
+                std::process::exit(0);
+            }
+            _ if arg.starts_with('-') => return Err(format!("unknown option {arg}")),
+            _ => args.tasks.push(arg),
+        }
+    }
+
+    if args.tasks.is_empty() {
+        Err("no tasks given".to_string())
+    } else {
+        Ok(args)
+    }
+}
+
+fn main() {
+    let args = parse_args().unwrap_or_else(|e| {
+        eprintln!("error: {e}\n\n{USAGE}");
+        std::process::exit(2);
+    });
+    let fail = |e: String| -> ! {
+        eprintln!("error: {e}");
+        std::process::exit(2);
+    };
+
+    let config = match &args.config {
+        Some(path) => SynthConfig::load(path).unwrap_or_else(|e| fail(e.to_string())),
+        None => SynthConfig::default(),
+    };
+    let mut ranking = match &args.weights {
+        Some(path) => Ranking::load(path).unwrap_or_else(|e| fail(e.to_string())),
+        None => Ranking::default(),
+    };
+
+    let tasks = args
+        .tasks
+        .iter()
+        .map(|path| {
+            Task::load(path)
+                .map(|task| task.examples)
+                .unwrap_or_else(|e| fail(format!("{path}: {e}")))
+        })
+        .collect::<Vec<_>>();
+
+    let (trained, solved) = ranking.train::<StringDsl>(&tasks, &config, args.training);
+    if trained == 0 {
+        eprintln!("no task has both programs that fit every example and ones that don't");
+    } else {
+        for (epoch, solved) in solved.iter().enumerate() {
+            eprintln!(
+                "epoch {}: {solved} of {trained} tasks ranked right",
+                epoch + 1
+            );
+        }
+    }
+
+    match &args.output {
+        Some(path) => ranking.save(path).unwrap_or_else(|e| fail(e.to_string())),
+        None => println!("{}", serde_json::to_string_pretty(&ranking).unwrap()),
+    }
+}
@@ -0,0 +1,19 @@
+pub mod synth;
+pub mod task;
+
+pub use synth::bank::Bank;
+pub use synth::budget::{Budget, Exhausted, Progress};
+pub use synth::config::{Bools, SynthConfig};
+pub use synth::disambiguate::{distinguishing_input, Answer, Question};
+pub use synth::dsl::{ASTOf, Dsl, StringDsl, VSAOf};
+pub use synth::rank::{Ranking, Training};
+pub use synth::ranked::Ranked;
+pub use synth::sample::{Sampler, Weighting};
+pub use synth::token::{Class, Pattern, Token};
+pub use synth::vsa::{Clusters, Cost, Fun, InputLit, Language, Lit, Typ, VSAStats};
+pub use synth::witness::{Witness, WitnessCtx, WitnessRegistry};
+pub use synth::{
+    bottom_up, learn, learn_to_depth, learn_with, regex, top_down, top_down_observed,
+    top_down_with, top_down_within, AST, VSA,
+};
+pub use task::{Settings, Task, TaskError};
@@ -3,37 +3,26 @@ use egui_macroquad::macroquad;
 use macroquad::prelude::*;
 
 mod main_state;
-mod synth;
 mod util;
 mod vsa_state;
 
-use synth::vsa::{Fun, Lit, AST, VSA};
-use vsa_state::RichVSA;
-
-use std::rc::Rc;
+use interactive_vsa::synth::vsa::Lit;
+use interactive_vsa::Task;
 
 #[macroquad::main("Cloth")]
 async fn main() -> Result<(), std::io::Error> {
-    // use crate::synth::vsa::Lit;
-    let examples = vec![
-        (
-            Lit::StringConst("I have 17 cookies".to_string()),
-            Lit::StringConst("17".to_string()),
-        ),
-        // (
-        //     Lit::StringConst("Give me at least 3 cookies".to_string()),
-        //     Lit::StringConst("3".to_string()),
-        // ),
-        // (
-        //     Lit::StringConst("This number is 489".to_string()),
-        //     Lit::StringConst("489".to_string()),
-        // ),
-    ];
-
-    let (vsa, ast) = synth::top_down(&examples);
-    let flat_vsa = crate::synth::vsa::VSA::flatten(std::rc::Rc::new(vsa));
-    println!("{}", ast.unwrap());
-    println!("{:?}", flat_vsa);
+    // optionally start from a .json or .csv task file instead of
+    // the default example
+    let task = match std::env::args().nth(1) {
+        Some(path) => Task::load(&path).unwrap_or_else(|e| {
+            eprintln!("error: {e}");
+            std::process::exit(1);
+        }),
+        None => Task::new(vec![(
+            Lit::StringConst("First Last".to_string()),
+            Lit::StringConst("F.L.".to_string()),
+        )]),
+    };
 
     egui_macroquad::cfg(|egui_ctx| {
         egui_ctx.style_mut(|style| {
@@ -43,31 +32,8 @@ async fn main() -> Result<(), std::io::Error> {
         });
     });
 
-    // let vsa = Rc::new({
-    //     let mut set = std::collections::HashSet::new();
-    //     set.insert(Rc::new(AST::Lit(Lit::StringConst("First Last".to_string()))));
-    //     // VSA::Leaf(set)
-    //     VSA::Union(vec![
-    //                Rc::new(VSA::Leaf(set.clone())),
-    //                Rc::new(VSA::Leaf(set))
-    //     ])
-    // });
-    let unlearned_vsa = Rc::new(VSA::<_, Fun>::Unlearned {
-        start: Lit::StringConst("First Last".to_string()),
-        goal: Lit::StringConst("F.L.".to_string()),
-    });
-    let mut vsas = Vec::new();
-    vsas.push(
-        RichVSA::new(
-            unlearned_vsa,
-            Lit::StringConst("First Last".to_string()),
-            Lit::StringConst("F.L.".to_string()),
-            Vec2::new(screen_width() / 2.0, 100.0),
-            Vec::new(),
-        )
-        .editable(),
-    );
-    let mut main_state = main_state::MainState::new(vsas);
+    let mut main_state = main_state::MainState::new(Vec::new());
+    main_state.load_task(&task);
     loop {
         next_frame().await;
         main_state.draw();
@@ -1,6 +1,7 @@
-use crate::synth::vsa::*;
 use egui_macroquad::egui;
 use egui_macroquad::macroquad::prelude::*;
+use interactive_vsa::synth::vsa::*;
+use interactive_vsa::{StringDsl, SynthConfig, Task};
 
 use crate::vsa_state::*;
 
@@ -18,9 +19,10 @@ pub struct MainState {
     pub vsa_labels: bool,
     pub current_tool: Tool,
     pub learn_depth: usize,
-    pub search_depth: usize,
+    pub config: SynthConfig<StringDsl>,
     pub messages: Vec<Message>,
     pub show_help: bool,
+    pub task_path: String,
 }
 
 pub struct Message {
@@ -48,9 +50,28 @@ impl MainState {
             vsa_labels: false,
             current_tool: Tool::Drag,
             learn_depth: 1,
-            search_depth: 3,
+            config: SynthConfig::default(),
             messages: vec![],
             show_help: true,
+            task_path: String::new(),
+        }
+    }
+
+    pub fn load_task(&mut self, task: &Task) {
+        self.vsas = vec![RichVSA::from_task(task, vec2(screen_width() / 2.0, 100.0))];
+        self.learn_depth = task.settings.learn_depth;
+        self.config = task.config.clone();
+    }
+
+    pub fn open_task(&mut self) {
+        match Task::load(&self.task_path) {
+            Ok(task) => self.load_task(&task),
+            Err(e) => self.messages.push(Message {
+                text: egui::RichText::new(e.to_string())
+                    .size(24.0)
+                    .color(egui::Color32::RED),
+                remaining_frames: 600,
+            }),
         }
     }
 
@@ -128,19 +149,19 @@ impl MainState {
                             for child in parent
                                 .children
                                 .iter()
-                                .filter(|c| !std::rc::Rc::ptr_eq(&c.vsa, &child))
+                                .filter(|c| !std::sync::Arc::ptr_eq(&c.vsa, &child))
                             {
                                 kill_vsas.push(child.vsa.clone());
                             }
                             parent
                                 .children
-                                .retain(|c| std::rc::Rc::ptr_eq(&c.vsa, &child));
+                                .retain(|c| std::sync::Arc::ptr_eq(&c.vsa, &child));
                         } else
                         /* if self.current_tool == Tool::Prune */
                         {
                             parent
                                 .children
-                                .retain(|c| !std::rc::Rc::ptr_eq(&c.vsa, &child));
+                                .retain(|c| !std::sync::Arc::ptr_eq(&c.vsa, &child));
                             kill_vsas.push(child);
                         }
 
@@ -150,10 +171,10 @@ impl MainState {
 
                         for vsa in kill_vsas {
                             let new_vsa = VSA::empty();
-                            let vsa_rc_mut = std::rc::Rc::as_ptr(&vsa) as *mut VSA<Lit, Fun>;
+                            let vsa_arc_mut = std::sync::Arc::as_ptr(&vsa) as *mut VSA<Lit, Fun>;
                             // safety: probably
                             unsafe {
-                                std::ptr::write(vsa_rc_mut, new_vsa);
+                                std::ptr::write(vsa_arc_mut, new_vsa);
                             }
                         }
                     }
@@ -167,14 +188,13 @@ impl MainState {
                     .iter_mut()
                     .find_map(|vsa| vsa.find_clicked_node(pos, egui_ctx));
                 if let Some(clicked_node) = clicked_node {
-                    let ast = clicked_node.vsa.pick_best(|ast| ast.cost());
-                    if let Some(ast) = ast {
+                    if let Some((_, ast)) = clicked_node.vsa.pick_best() {
                         let new_vsa = VSA::singleton(ast);
-                        let vsa_rc_mut =
-                            std::rc::Rc::as_ptr(&clicked_node.vsa) as *mut VSA<Lit, Fun>;
+                        let vsa_arc_mut =
+                            std::sync::Arc::as_ptr(&clicked_node.vsa) as *mut VSA<Lit, Fun>;
                         // safety: probably
                         unsafe {
-                            std::ptr::write(vsa_rc_mut, new_vsa);
+                            std::ptr::write(vsa_arc_mut, new_vsa);
                         }
                         clicked_node.children.clear();
                         self.current_tool = Tool::Drag;
@@ -222,12 +242,7 @@ impl MainState {
             // });
 
             for vsa in &mut self.vsas {
-                vsa.draw(
-                    self.vsa_labels,
-                    self.learn_depth,
-                    self.search_depth,
-                    egui_ctx,
-                );
+                vsa.draw(self.vsa_labels, self.learn_depth, &self.config, egui_ctx);
                 // draw_vsa(vsa.vsa.clone(), Vec2::new(100.0, 100.0), &vsa.input, None, egui_ctx);
             }
 
@@ -250,9 +265,14 @@ impl MainState {
                             .text("Learn Depth"),
                     );
                     ui.add(
-                        egui::widgets::Slider::new(&mut self.search_depth, 1..=9)
-                            .text("Search Depth"),
+                        egui::widgets::Slider::new(&mut self.config.max_size, 1..=9)
+                            .text("Max Size"),
                     );
+
+                    ui.text_edit_singleline(&mut self.task_path);
+                    if ui.button("Open Task").clicked() {
+                        self.open_task();
+                    }
                 });
             });
 
@@ -1,120 +1,91 @@
 use std::{
-    collections::{HashMap, HashSet},
+    collections::{HashMap, HashSet, VecDeque},
     num::NonZeroUsize,
-    rc::Rc,
-    sync::RwLock,
+    sync::{Arc, RwLock},
 };
 
-use itertools::iproduct;
+use itertools::Itertools;
 use lru::LruCache;
 use regex::Regex;
 
 pub mod bank;
+pub mod budget;
+pub mod config;
+pub mod disambiguate;
+pub mod dsl;
+pub mod rank;
+pub mod ranked;
+pub mod sample;
+pub mod token;
 pub mod vsa;
+pub mod witness;
 
 use bank::Bank;
-use vsa::{Cost, Fun, Lit};
+use budget::{Budget, Exhausted};
+use config::SynthConfig;
+use dsl::{ASTOf, Dsl, StringDsl, VSAOf};
+use vsa::{Fun, Lit};
+use witness::WitnessCtx;
 
 use lazy_static::lazy_static;
 
 pub type VSA = vsa::VSA<Lit, Fun>;
 pub type AST = vsa::AST<Lit, Fun>;
 
-macro_rules! loc_pat {
-    () => {
-        AST::Lit(Lit::LocConst(_) | Lit::LocEnd)
-            | AST::App {
-                fun: Fun::Find | Fun::LocAdd | Fun::LocSub,
-                ..
-            }
-    };
-}
-
 lazy_static! {
     // TODO: figure out ideal cache size
     pub static ref CACHE: RwLock<LruCache<String, Regex>> = RwLock::new(LruCache::new(NonZeroUsize::new(2000).unwrap()));
-    pub static ref EMPTY_REGEX: Regex = Regex::new(".").unwrap();
 }
 
-pub fn regex(s: &String) -> Regex {
+// only patterns that compile are cached
+pub fn regex(s: &str) -> Result<Regex, regex::Error> {
     let mut cache_writer = CACHE.write().unwrap();
-    if cache_writer.contains(s) {
-        cache_writer.get(s).unwrap().clone()
-    } else {
-        // cache_writer.push(s.clone(), Regex::new(s).unwrap_or(regex(&".".to_string())));
-        cache_writer.push(s.clone(), Regex::new(s).unwrap_or(EMPTY_REGEX.clone()));
-        cache_writer.get(s).unwrap().clone()
+    if let Some(re) = cache_writer.get(s) {
+        return Ok(re.clone());
     }
+    let re = Regex::new(s)?;
+    cache_writer.push(s.to_string(), re.clone());
+    Ok(re)
 }
 
 // TODO:
 // add a substitute function
 
+// the largest program the bottom up fallback in `learn` will enumerate
+const FALLBACK_SIZE: usize = 4;
+
 pub fn top_down(examples: &[(Lit, Lit)]) -> (VSA, Option<AST>) {
-    let mut bank = Bank::new();
-    let mut regex_bank = Bank::new();
-    let mut all_cache = HashMap::new();
-
-    let mut char_sets = examples.iter().map(|(inp, out)| match (inp, out) {
-        (Lit::StringConst(inp), Lit::StringConst(out)) => inp
-            .chars()
-            .chain(out.chars())
-            .filter(|c| !c.is_alphanumeric())
-            .map(|c| match c {
-                '.' => Lit::StringConst("\\.".to_string()),
-                '{' => Lit::StringConst("\\{".to_string()),
-                '}' => Lit::StringConst("\\{".to_string()),
-                _ => Lit::StringConst(c.to_string()),
-            })
-            .collect::<HashSet<_>>(),
-        _ => HashSet::new(),
-    });
-    let intersection = char_sets
-        .next()
-        .map(|s1| {
-            s1.iter()
-                .filter(|c| char_sets.clone().all(|s2| s2.contains(c)))
-                .cloned()
-                .collect::<Vec<_>>()
-        })
-        .unwrap_or_default();
-
-    // dbg!(&intersection);
-
-    // TODO:
-    // a cache that is only applied to regexes
-    for prim in [
-        Lit::Input,
-        Lit::StringConst("".to_string()),
-        Lit::StringConst(" ".to_string()),
-        Lit::StringConst(".".to_string()),
-        Lit::LocConst(0),
-        Lit::LocConst(1),
-        Lit::LocEnd,
-    ]
-    .into_iter()
-    .chain(intersection.clone().into_iter())
-    {
-        bank.size_mut(1).push(AST::Lit(prim.clone()));
-        all_cache.insert(
-            std::iter::repeat(prim.clone())
-                .take(examples.len())
-                .collect(),
-            Rc::new(VSA::singleton(AST::Lit(prim.clone()))),
-        );
-    }
+    top_down_with::<StringDsl>(examples, &SynthConfig::default())
+}
 
-    for prim in [
-        Lit::StringConst("\\d".to_string()),
-        Lit::StringConst("\\b".to_string()),
-        Lit::StringConst("[a-z]".to_string()),
-        Lit::StringConst("[A-Z]".to_string()),
-    ]
-    .into_iter()
-    .chain(intersection.into_iter())
-    {
-        regex_bank.size_mut(1).push(AST::Lit(prim.clone()));
-    }
+pub fn top_down_with<D: Dsl>(
+    examples: &[(D::Lit, D::Lit)],
+    config: &SynthConfig<D>,
+) -> (VSAOf<D>, Option<ASTOf<D>>) {
+    let (vsa, ast, _) = top_down_within(examples, config, &config.budget());
+    (vsa, ast)
+}
+
+// Like `top_down_with`, but also returns why it stopped early if the budget
+// ran out. The VSA is then only partially learned.
+pub fn top_down_within<D: Dsl>(
+    examples: &[(D::Lit, D::Lit)],
+    config: &SynthConfig<D>,
+    budget: &Budget,
+) -> (VSAOf<D>, Option<ASTOf<D>>, Option<Exhausted>) {
+    top_down_observed(examples, config, budget, |_, _, _| {})
+}
+
+// Like `top_down_within`, but calls `observe` with the program size, the
+// number of examples and the VSA after learning the first example and after
+// intersecting with each of the others, to watch the ambiguity go down.
+pub fn top_down_observed<D: Dsl>(
+    examples: &[(D::Lit, D::Lit)],
+    config: &SynthConfig<D>,
+    budget: &Budget,
+    mut observe: impl FnMut(usize, usize, &VSAOf<D>),
+) -> (VSAOf<D>, Option<ASTOf<D>>, Option<Exhausted>) {
+    let (mut bank, mut token_bank, mut all_cache) = config.banks(examples, examples.len());
 
     // let test_prog = AST::JS {
     //     code: "X.upper()".to_string(),
@@ -125,666 +96,676 @@ pub fn top_down(examples: &[(Lit, Lit)]) -> (VSA, Option<AST>) {
     // let outputs = examples.iter().map(|(inp, _)| test_prog.eval(inp));
     // all_cache.insert(
     //     outputs.collect(),
-    //     Rc::new(VSA::singleton(test_prog.clone())),
+    //     Arc::new(VSA::singleton(test_prog.clone())),
     // );
 
-    let enable_bools = examples
-        .iter()
-        .any(|(_, out)| matches!(out, Lit::BoolConst(_)));
+    let enable_bools = config.enable_bools(examples);
 
     let mut size = 1;
     let inps = examples.iter().map(|(inp, _)| inp);
 
     let mut best_vsa = None;
-    while size <= 6 {
-        bottom_up(
+    // the first conditional found, only used once no single program fits
+    // every example up to `max_size`
+    let mut conditional = None;
+    while size <= config.max_size {
+        let grown = bottom_up::<D>(
             inps.clone(),
             size,
             &mut all_cache,
             &mut bank,
-            &mut regex_bank,
+            &mut token_bank,
+            &config.operators,
             enable_bools,
+            budget,
         );
+        if grown.is_err() {
+            break;
+        }
         // dbg!(&bank);
         // dbg!(bank.total_entries());
         let mut ex_vsas = examples.iter().enumerate().map(|(i, (inp, out))| {
-            let mut cache: HashMap<Lit, Rc<VSA>> = HashMap::new();
+            let mut cache: HashMap<D::Lit, Arc<VSAOf<D>>> = HashMap::new();
             for (outs, vsa) in all_cache.iter() {
                 if let Some(v) = cache.get_mut(&outs[i]) {
-                    *v = Rc::new(VSA::unify(vsa.clone(), v.clone()));
+                    *v = Arc::new(vsa::VSA::unify(vsa.clone(), v.clone()));
                 } else {
                     cache.insert(outs[i].clone(), vsa.clone());
                 }
             }
 
-            learn(inp, out, &mut cache, &bank)
+            learn_with::<D>(inp, out, &cache, &bank, config, usize::MAX, budget)
         });
 
         let mut res = ex_vsas.next().unwrap();
+        let mut learned = vec![res.clone()];
+        observe(size, 1, &res);
 
         // TODO:
         // instead of pick_best, pick the best 10, and then
         // check if it works on all examples
-        for vsa in ex_vsas {
-            if let Some(prog) = res.pick_best(|ast| ast.cost()) {
+        for (i, vsa) in ex_vsas.by_ref().enumerate() {
+            learned.push(vsa.clone());
+            if budget.exhausted().is_some() {
+                break;
+            }
+
+            if let Some(prog) = best_program(&res, examples, config) {
                 if examples.iter().all(|(inp, out)| prog.eval(inp) == *out) {
                     break;
                 };
             }
 
-            res = Rc::new(res.intersect(vsa.as_ref()));
+            res = Arc::new(res.intersect_within(vsa.as_ref(), budget));
+            observe(size, i + 2, &res);
         }
 
-        match res.pick_best(|ast| ast.cost()) {
-            ast @ Some(_) => return (res.clone().as_ref().clone(), ast),
+        // an over approximated intersection, see `intersect_checked`, keeps
+        // programs that weren't checked against every example, so the best
+        // one is checked here
+        let best = best_program(&res, examples, config)
+            .filter(|prog| examples.iter().all(|(inp, out)| prog.eval(inp) == *out));
+
+        if best.is_none() && conditional.is_none() && budget.exhausted().is_none() {
+            learned.extend(ex_vsas);
+            conditional = learn_conditional(examples, &learned, &all_cache, config, budget);
+        }
+
+        match best {
+            Some(ast) => return (res.clone().as_ref().clone(), Some(ast), budget.exhausted()),
             None => {
                 best_vsa = Some(res);
+                if budget.exhausted().is_some() {
+                    break;
+                }
                 size += 1;
             }
         }
     }
 
-    (best_vsa.unwrap().clone().as_ref().clone(), None)
+    if let Some((vsa, ast)) = conditional {
+        return (vsa, Some(ast), budget.exhausted());
+    }
+
+    let vsa = match best_vsa {
+        Some(vsa) => vsa.as_ref().clone(),
+        // ran out of budget before learning anything
+        None => vsa::VSA::Unlearned {
+            start: examples[0].0.clone(),
+            goal: examples[0].1.clone(),
+            others: examples[1..].to_vec(),
+        },
+    };
+    (vsa, None, budget.exhausted())
 }
 
-// TODO:
-// there's still an issue with cycles here
-// maybe still needs a queue
-fn learn(inp: &Lit, out: &Lit, cache: &mut HashMap<Lit, Rc<VSA>>, bank: &Bank<AST>) -> Rc<VSA> {
-    // dbg!();
-    let mut unifier = Vec::new();
-    if let Some(res) = cache.get(out) {
-        unifier.push(res.as_ref().clone());
-        // return res.clone();
+// the most branches a conditional learned by `top_down` can have
+const MAX_BRANCHES: usize = 3;
+
+// the most programs of a branch for a single example that are checked for
+// depending on the input
+const MAX_SINGLE_BRANCH_PROGRAMS: usize = 1000;
+
+// When no single program fits every example, tries `if p1 then b1 else if
+// p2 then b2 else b3`. Each example joins the first group of examples whose
+// programs still fit it too. A group of one example could otherwise get a
+// branch that's just its output, so the branch of a lone example has to give
+// something else on one of `D::mutate_input`'s inputs. `learned` has the
+// programs that fit each example.
+fn learn_conditional<D: Dsl>(
+    examples: &[(D::Lit, D::Lit)],
+    learned: &[Arc<VSAOf<D>>],
+    cache: &HashMap<Vec<D::Lit>, Arc<VSAOf<D>>>,
+    config: &SynthConfig<D>,
+    budget: &Budget,
+) -> Option<(VSAOf<D>, ASTOf<D>)> {
+    let (op, then_lit) = D::conditional().filter(|(op, _)| config.operators.contains(op))?;
+
+    let mut groups: Vec<(Vec<usize>, Arc<VSAOf<D>>)> = Vec::new();
+    for (i, vsa) in learned.iter().enumerate() {
+        let joined = groups.iter_mut().any(|(members, group)| {
+            let (both, approximate) = group.intersect_checked(vsa, budget);
+            let both = Arc::new(both);
+            // an over approximated group is checked on its examples
+            let fits = best_program(&both, examples, config).is_some_and(|prog| {
+                !approximate
+                    || members
+                        .iter()
+                        .chain([&i])
+                        .all(|&j| prog.eval(&examples[j].0) == examples[j].1)
+            });
+            if fits {
+                members.push(i);
+                *group = both;
+            }
+            fits
+        });
+        if !joined {
+            groups.push((vec![i], vsa.clone()));
+        }
+        if groups.len() > MAX_BRANCHES || budget.exhausted().is_some() {
+            return None;
+        }
     }
-
-    macro_rules! universal_witness {
-        ($p:pat) => {
-            bank.entries
-                .iter()
-                .flat_map(|entry| entry.iter().filter(|ast| matches!(ast, $p)))
-        };
+    if groups.len() < 2 {
+        return None;
     }
+    let groups = groups
+        .into_iter()
+        .map(|(members, vsa)| {
+            if members.len() > 1 {
+                return Some((members, vsa));
+            }
+            let (inp, out) = &examples[members[0]];
+            let mutated = D::mutate_input(inp);
+            let programs = vsa::VSA::ranked(&vsa)
+                .take(MAX_SINGLE_BRANCH_PROGRAMS)
+                .filter(|ast| mutated.iter().any(|inp| ast.eval(inp) != *out))
+                .map(Arc::new)
+                .collect::<HashSet<_>>();
+            (!programs.is_empty()).then(|| (members, Arc::new(vsa::VSA::Leaf(programs))))
+        })
+        .collect::<Option<Vec<_>>>()?;
+
+    let inputs = examples
+        .iter()
+        .map(|(inp, _)| inp.clone())
+        .collect::<Vec<_>>();
+    let goal = |members: &[usize]| examples[members[0]].1.clone();
 
-    macro_rules! multi_match {
-        ($v:expr, $($p:pat $(if $guard:expr)? => $res:expr),*) => {
-            $(
-                match $v {
-                    $p $(if $guard)? => $res,
-                    _ => {},
+    // the branches can be in any order, the predicates might only tell some
+    // of the groups apart from the rest or be cheaper one way round
+    let orders = groups
+        .iter()
+        .permutations(groups.len())
+        .filter_map(|order| {
+            let (last_members, last) = order.last().unwrap();
+            let mut vsa = last.clone();
+            let mut else_goal = goal(last_members);
+            for (j, (members, branch)) in order.iter().enumerate().rev().skip(1) {
+                let targets = (0..examples.len())
+                    .map(|i| {
+                        if members.contains(&i) {
+                            Some(true)
+                        } else if order[j + 1..].iter().any(|(later, _)| later.contains(&i)) {
+                            Some(false)
+                        } else {
+                            None
+                        }
+                    })
+                    .collect::<Vec<_>>();
+                let preds = D::predicates(&inputs, cache, &targets, budget)
+                    .into_iter()
+                    .map(Arc::new)
+                    .collect::<Vec<_>>();
+                if budget.exhausted().is_some() {
+                    return None;
                 }
-            )*
-        };
+                let pred = match preds.len() {
+                    0 => return None,
+                    1 => preds[0].clone(),
+                    _ => Arc::new(vsa::VSA::Union(preds)),
+                };
+                vsa = Arc::new(vsa::VSA::Join {
+                    op,
+                    children: vec![pred, branch.clone(), vsa],
+                    children_goals: vec![then_lit.clone(), goal(members), else_goal],
+                });
+                else_goal = goal(members);
+            }
+            Some(vsa)
+        })
+        .collect::<Vec<_>>();
+
+    let vsa = match orders.len() {
+        0 => return None,
+        1 => orders[0].clone(),
+        _ => Arc::new(vsa::VSA::Union(orders)),
+    };
+    let ast = best_program(&vsa, examples, config)?;
+    examples
+        .iter()
+        .all(|(inp, out)| ast.eval(inp) == *out)
+        .then(|| (vsa.as_ref().clone(), ast))
+}
+
+// the cheapest program, or the best one by the config's ranking
+fn best_program<D: Dsl>(
+    vsa: &Arc<VSAOf<D>>,
+    examples: &[(D::Lit, D::Lit)],
+    config: &SynthConfig<D>,
+) -> Option<ASTOf<D>> {
+    match &config.ranking {
+        Some(ranking) => ranking.pick_best::<D>(vsa, examples).map(|(_, ast)| ast),
+        None => vsa.pick_best().map(|(_, ast)| ast),
     }
+}
 
-    multi_match!((out, inp),
-    // (Lit::StringConst(s), _) if s.as_str() == " " => {
-    //     unifier.push(VSA::singleton(AST::Lit(Lit::StringConst(" ".to_string()))))
-    // },
-    // (Lit::StringConst(s), _) if s.as_str() == "." => {
-    //     unifier.push(VSA::singleton(AST::Lit(Lit::StringConst(".".to_string()))))
-    // },
-    //
-    // TODO:
-    // this makes it impossible to learn in one shot
-    (Lit::StringConst(s), _) => {
-        unifier.push(VSA::singleton(AST::Lit(Lit::StringConst(s.clone()))))
-    },
-
-    (Lit::BoolConst(b), _) => {
-        unifier.push(VSA::singleton(AST::Lit(Lit::BoolConst(*b))))
-    },
-
-    (Lit::LocConst(n), _) => {
-        unifier.push(VSA::singleton(AST::Lit(Lit::LocConst(*n))))
-    },
-
-    (Lit::LocConst(n), Lit::StringConst(inp_str)) if inp_str.len() == *n => {
-        unifier.push(VSA::singleton(AST::Lit(Lit::LocEnd)));
-    },
-
-    (Lit::BoolConst(b), _) => {
-        let s = iproduct!(universal_witness!(loc_pat!()), universal_witness!(loc_pat!())).map(|(lhs, rhs)| {
-            AST::App {
-                fun: Fun::Equal,
-                args: vec![lhs.clone(), rhs.clone()],
-            }
-        }).map(Rc::new).collect();
-        unifier.push(VSA::Leaf(s));
-    },
-
-    (Lit::StringConst(s), Lit::StringConst(inp_str)) if s.contains(inp_str) => {
-        let re = regex(inp_str);
-
-        re.find_iter(s)
-            .map(|m| {
-                let start = m.start();
-                let end = m.end();
-                let start_lit = Lit::StringConst(s[0..start].to_string());
-                let end_lit = Lit::StringConst(s[end..].to_string());
-                let start_vsa = learn(inp, &start_lit, cache, bank);
-                let end_vsa = learn(inp, &end_lit, cache, bank);
-                // dbg!(start, end, s[0..start].to_string(), s[end..].to_string(), start_vsa.clone(), end_vsa.clone());
-                // TODO: maybe add a simplify function to the AST
-                VSA::Join {
-                    op: Fun::Concat,
-                    children: vec![
-                        start_vsa,
-                        Rc::new(VSA::Join {
-                            op: Fun::Concat,
-                            children: vec![
-                                learn(inp, &Lit::Input, cache, bank),
-                                end_vsa,
-                            ],
-                            children_goals: vec![Lit::Input],
-                        }),
-                    ],
-                    children_goals: vec![start_lit, end_lit],
-                }
-            })
-        .for_each(|vsa| unifier.push(vsa));
-        },
+pub fn learn<D: Dsl>(
+    inp: &D::Lit,
+    out: &D::Lit,
+    cache: &mut HashMap<D::Lit, Arc<VSAOf<D>>>,
+    bank: &Bank<ASTOf<D>>,
+) -> Arc<VSAOf<D>> {
+    learn_with::<D>(
+        inp,
+        out,
+        cache,
+        bank,
+        &SynthConfig::default(),
+        usize::MAX,
+        &Budget::unlimited(),
+    )
+}
 
-        (Lit::StringConst(s), Lit::StringConst(inp_str)) if inp_str.contains(s) => {
-            let re = regex(s);
-            let start = inp_str.find(s).unwrap();
-            let end = start + s.len();
-            // dbg!(s, start, end);
-            let start_lit = Lit::LocConst(start);
-            let end_lit = Lit::LocConst(end);
-            let start_vsa = learn(inp, &start_lit, cache, bank);
-            let end_vsa = learn(inp, &end_lit, cache, bank);
-            unifier.push(VSA::Join {
-                op: Fun::Slice,
-                children: vec![
-                    start_vsa,
-                    end_vsa,
-                ],
-                children_goals: vec![start_lit, end_lit],
-            });
-        },
+pub fn learn_to_depth<D: Dsl>(
+    inp: &D::Lit,
+    out: &D::Lit,
+    cache: &mut HashMap<D::Lit, Arc<VSAOf<D>>>,
+    bank: &Bank<ASTOf<D>>,
+    depth: usize,
+) -> Arc<VSAOf<D>> {
+    learn_with::<D>(
+        inp,
+        out,
+        cache,
+        bank,
+        &SynthConfig::default(),
+        depth,
+        &Budget::unlimited(),
+    )
+}
 
-        (Lit::StringConst(s), Lit::StringConst(inp_str)) if !inp_str.contains(s) && !s.contains(inp_str) => {
-            let set = (1..s.len())
-                .map(|i| VSA::Join {
-                    op: Fun::Concat,
-                    children: vec![
-                        learn(
-                            inp,
-                            &Lit::StringConst(s[0..i].to_string()),
-                            cache,
-                            bank,
-                        ),
-                        learn(
-                            inp,
-                            &Lit::StringConst(s[i..].to_string()),
-                            cache,
-                            bank,
-                        ),
-                    ],
-                    children_goals: vec![Lit::StringConst(s[0..i].to_string()), Lit::StringConst(s[i..].to_string())],
-                })
-                .map(Rc::new)
-                .collect();
+// Learns with the config's witnesses and only the operators and tokens it
+// allows, see `learn_bottom_up`
+pub fn learn_with<D: Dsl>(
+    inp: &D::Lit,
+    out: &D::Lit,
+    cache: &HashMap<D::Lit, Arc<VSAOf<D>>>,
+    bank: &Bank<ASTOf<D>>,
+    config: &SynthConfig<D>,
+    depth: usize,
+    budget: &Budget,
+) -> Arc<VSAOf<D>> {
+    let disabled = config.disabled_operators();
+    Learner::new(inp, cache, bank, config, &disabled, depth, budget).learn(out)
+}
 
-            unifier.push(VSA::Union(set));
+type Example<D> = (<D as Dsl>::Lit, <D as Dsl>::Lit);
+
+// Top down learning for a single input.
+//
+// Witnesses return VSAs whose subgoals are left as `VSA::Unlearned` holes.
+// First the goals are expanded breadth first from a worklist, so every goal
+// is only expanded once, at the shallowest depth it's reachable from. Then the
+// holes are filled in from a memo table, so repeated goals share the same node
+// and the result is a DAG. Alternatives that lead back to a goal which is still
+// being built are cyclic and get dropped. If the budget runs out, the goals
+// that haven't been expanded yet are left as holes.
+//
+// Holes for a lambda start from other inputs than the one being learned for.
+// They're learned separately for each of their examples, against the bank's
+// programs evaluated on that example's input, and intersected.
+struct Learner<'a, D: Dsl> {
+    inp: &'a D::Lit,
+    cache: &'a HashMap<D::Lit, Arc<VSAOf<D>>>,
+    bank: &'a Bank<ASTOf<D>>,
+    config: &'a SynthConfig<D>,
+    disabled: &'a [D::Fun],
+    max_depth: usize,
+    budget: &'a Budget,
+    expansions: HashMap<D::Lit, Vec<VSAOf<D>>>,
+    memo: HashMap<D::Lit, Arc<VSAOf<D>>>,
+    in_progress: HashSet<D::Lit>,
+    lambdas: HashMap<Example<D>, Arc<VSAOf<D>>>,
+}
+
+impl<'a, D: Dsl> Learner<'a, D> {
+    fn new(
+        inp: &'a D::Lit,
+        cache: &'a HashMap<D::Lit, Arc<VSAOf<D>>>,
+        bank: &'a Bank<ASTOf<D>>,
+        config: &'a SynthConfig<D>,
+        disabled: &'a [D::Fun],
+        max_depth: usize,
+        budget: &'a Budget,
+    ) -> Self {
+        Learner {
+            inp,
+            cache,
+            bank,
+            config,
+            disabled,
+            max_depth,
+            budget,
+            expansions: HashMap::new(),
+            memo: HashMap::new(),
+            in_progress: HashSet::new(),
+            lambdas: HashMap::new(),
         }
+    }
 
-    // TODO: figure out the index
-    // (Lit::LocConst(n), Lit::StringConst(s)) if s.chars().nth(*n).is_some_and(|ch| ch == ' ') => {
-    //     let lhs = Rc::new(VSA::singleton(AST::Lit(Lit::Input)));
-    //     let space = cache.get(&Lit::StringConst(" ".to_string())).unwrap().clone();
-    //     let wb = cache.get(&Lit::StringConst("\\b".to_string())).unwrap().clone();
-
-    //     unifier.push(VSA::Join {
-    //         op: Fun::Find,
-    //         children: vec![lhs.clone(), space],
-    //     });
-
-    //     if s.chars().nth(n - 1).is_some_and(|ch| ch.is_alphanumeric()) {
-    //         unifier.push(VSA::Join {
-    //             op: Fun::Find,
-    //             children: vec![lhs, wb],
-    //         });
-    //     }
-    // }
-    );
-
-    let res = unifier
-        .into_iter()
-        .map(Rc::new)
-        .fold(Rc::new(VSA::empty()), |acc, x| Rc::new(VSA::unify(acc, x)));
+    fn learn(mut self, out: &D::Lit) -> Arc<VSAOf<D>> {
+        let mut worklist = VecDeque::from([(out.clone(), 0)]);
+        while let Some((goal, depth)) = worklist.pop_front() {
+            if depth >= self.max_depth || self.expansions.contains_key(&goal) {
+                continue;
+            }
+
+            let expansion = self.witnesses(&goal);
+            let nodes = expansion.iter().map(Self::nodes).sum();
+            if self.budget.spend_vsa_nodes(nodes).is_err() {
+                break;
+            }
+
+            for vsa in expansion.iter() {
+                Self::holes(self.inp, vsa, &mut |subgoal| {
+                    if !self.expansions.contains_key(subgoal) {
+                        worklist.push_back((subgoal.clone(), depth + 1));
+                    }
+                });
+            }
+            self.expansions.insert(goal, expansion);
+        }
 
-    match res.as_ref() {
-        VSA::Union(s) if s.is_empty() => todo!(), //bottom up?
-        _ => {}
+        // the root can't be part of a cycle yet
+        self.build(out).unwrap()
     }
 
-    // cache.insert(out.clone(), res.clone());
-    res
-}
+    fn hole(&self, goal: D::Lit) -> Arc<VSAOf<D>> {
+        Arc::new(vsa::VSA::Unlearned {
+            start: self.inp.clone(),
+            goal,
+            others: Vec::new(),
+        })
+    }
 
-pub fn learn_to_depth(
-    inp: &Lit,
-    out: &Lit,
-    cache: &mut HashMap<Lit, Rc<VSA>>,
-    bank: &Bank<AST>,
-    depth: usize,
-) -> Rc<VSA> {
-    // dbg!();
-    let mut unifier = Vec::new();
-    if let Some(res) = cache.get(out) {
-        unifier.push(res.as_ref().clone());
-        // return res.clone();
+    // the goals of the holes that start from `inp`, lambdas are learned later
+    fn holes(inp: &D::Lit, vsa: &VSAOf<D>, f: &mut impl FnMut(&D::Lit)) {
+        match vsa {
+            vsa::VSA::Unlearned { start, goal, .. } if start == inp => f(goal),
+            vsa::VSA::Unlearned { .. } | vsa::VSA::Leaf(_) => {}
+            vsa::VSA::Union(vsas) => vsas.iter().for_each(|vsa| Self::holes(inp, vsa, f)),
+            vsa::VSA::Join { children, .. } => {
+                children.iter().for_each(|vsa| Self::holes(inp, vsa, f))
+            }
+        }
     }
 
-    if depth == 0 {
-        return Rc::new(VSA::Unlearned {
-            start: inp.clone(),
-            goal: out.clone(),
-        });
+    fn nodes(vsa: &VSAOf<D>) -> usize {
+        match vsa {
+            vsa::VSA::Union(vsas) | vsa::VSA::Join { children: vsas, .. } => {
+                1 + vsas.iter().map(|vsa| Self::nodes(vsa)).sum::<usize>()
+            }
+            _ => 1,
+        }
     }
 
-    macro_rules! universal_witness {
-        ($p:pat) => {
-            bank.entries
+    // None if the goal is already being built further up, i.e. there's a cycle
+    fn build(&mut self, goal: &D::Lit) -> Option<Arc<VSAOf<D>>> {
+        if let Some(res) = self.memo.get(goal) {
+            return Some(res.clone());
+        }
+
+        // past the depth limit
+        let Some(expansion) = self.expansions.get(goal).cloned() else {
+            return Some(self.hole(goal.clone()));
+        };
+
+        if !self.in_progress.insert(goal.clone()) {
+            return None;
+        }
+
+        let res = if expansion.is_empty() {
+            // no witness applies, so fall back to enumerating
+            // programs for just this goal
+            learn_bottom_up::<D>(self.inp, goal, self.bank, self.config, self.budget)
+        } else {
+            expansion
                 .iter()
-                .flat_map(|entry| entry.iter().filter(|ast| matches!(ast, $p)))
+                .filter_map(|vsa| self.fill(vsa))
+                .fold(Arc::new(vsa::VSA::empty()), |acc, x| {
+                    Arc::new(vsa::VSA::unify(acc, x))
+                })
         };
+
+        self.in_progress.remove(goal);
+        self.memo.insert(goal.clone(), res.clone());
+        Some(res)
     }
 
-    macro_rules! multi_match {
-        ($v:expr, $($p:pat $(if $guard:expr)? => $res:expr),*) => {
-            $(
-                match $v {
-                    $p $(if $guard)? => $res,
-                    _ => {},
-                }
-            )*
-        };
+    fn fill(&mut self, vsa: &VSAOf<D>) -> Option<Arc<VSAOf<D>>> {
+        match vsa {
+            vsa::VSA::Unlearned { start, goal, .. } if start == self.inp => self.build(goal),
+            hole @ vsa::VSA::Unlearned { .. } => Some(self.lambda(hole)),
+            vsa::VSA::Leaf(_) => Some(Arc::new(vsa.clone())),
+            vsa::VSA::Union(vsas) => Some(Arc::new(vsa::VSA::Union(
+                vsas.iter().filter_map(|vsa| self.fill(vsa)).collect(),
+            ))),
+            vsa::VSA::Join {
+                op,
+                children,
+                children_goals,
+            } => {
+                let children = children
+                    .iter()
+                    .map(|vsa| self.fill(vsa))
+                    .collect::<Option<Vec<_>>>()?;
+                Some(Arc::new(vsa::VSA::Join {
+                    op: *op,
+                    children,
+                    children_goals: children_goals.clone(),
+                }))
+            }
+        }
     }
 
-    multi_match!((out, inp),
-    // (Lit::StringConst(s), _) if s.as_str() == " " => {
-    //     unifier.push(VSA::singleton(AST::Lit(Lit::StringConst(" ".to_string()))))
-    // },
-    // (Lit::StringConst(s), _) if s.as_str() == "." => {
-    //     unifier.push(VSA::singleton(AST::Lit(Lit::StringConst(".".to_string()))))
-    // },
-    //
-    // TODO:
-    // this makes it impossible to learn in one shot
-    (Lit::StringConst(s), _) => {
-        unifier.push(VSA::singleton(AST::Lit(Lit::StringConst(s.clone()))))
-    },
-
-    (Lit::BoolConst(b), _) => {
-        unifier.push(VSA::singleton(AST::Lit(Lit::BoolConst(*b))))
-    },
-
-    (Lit::LocConst(n), _) => {
-        unifier.push(VSA::singleton(AST::Lit(Lit::LocConst(*n))))
-    },
-
-    (Lit::LocConst(n), Lit::StringConst(inp_str)) if inp_str.len() == *n => {
-        unifier.push(VSA::singleton(AST::Lit(Lit::LocEnd)));
-    },
-
-    (Lit::BoolConst(b), _) => {
-        let s = iproduct!(universal_witness!(loc_pat!()), universal_witness!(loc_pat!())).map(|(lhs, rhs)| {
-            AST::App {
-                fun: Fun::Equal,
-                args: vec![lhs.clone(), rhs.clone()],
+    fn lambda(&mut self, hole: &VSAOf<D>) -> Arc<VSAOf<D>> {
+        let mut res: Option<Arc<VSAOf<D>>> = None;
+        for (inp, out) in hole.hole_examples() {
+            if self.budget.exhausted().is_some() {
+                return Arc::new(hole.clone());
             }
-        }).map(Rc::new).collect();
-        unifier.push(VSA::Leaf(s));
-    },
-
-    (Lit::StringConst(s), Lit::StringConst(inp_str)) if s.contains(inp_str) => {
-        let re = regex(inp_str);
-
-        re.find_iter(s)
-            .map(|m| {
-                let start = m.start();
-                let end = m.end();
-                let start_lit = Lit::StringConst(s[0..start].to_string());
-                let end_lit = Lit::StringConst(s[end..].to_string());
-                let start_vsa = learn_to_depth(inp, &start_lit, cache, bank, depth - 1);
-                let end_vsa = learn_to_depth(inp, &end_lit, cache, bank, depth - 1);
-                // dbg!(start, end, s[0..start].to_string(), s[end..].to_string(), start_vsa.clone(), end_vsa.clone());
-                // TODO: maybe add a simplify function to the AST
-                VSA::Join {
-                    op: Fun::Concat,
-                    children: vec![
-                        start_vsa,
-                        Rc::new(VSA::Join {
-                            op: Fun::Concat,
-                            children: vec![
-                                learn_to_depth(inp, &Lit::Input, cache, bank, depth - 1),
-                                end_vsa,
-                            ],
-                            children_goals: vec![Lit::Input],
-                        }),
-                    ],
-                    children_goals: vec![start_lit, end_lit],
+
+            let key = (inp, out);
+            let vsa = match self.lambdas.get(&key) {
+                Some(vsa) => vsa.clone(),
+                None => {
+                    let (inp, out) = &key;
+                    let mut cache: HashMap<D::Lit, Arc<VSAOf<D>>> = HashMap::new();
+                    for ast in self.bank.entries.iter().flatten() {
+                        let vsa = Arc::new(vsa::VSA::singleton(ast.clone()));
+                        let out = ast.eval(inp);
+                        if let Some(v) = cache.get_mut(&out) {
+                            *v = Arc::new(vsa::VSA::unify(v.clone(), vsa));
+                        } else {
+                            cache.insert(out, vsa);
+                        }
+                    }
+                    let vsa = Learner::new(
+                        inp,
+                        &cache,
+                        self.bank,
+                        self.config,
+                        self.disabled,
+                        self.max_depth,
+                        self.budget,
+                    )
+                    .learn(out);
+                    self.lambdas.insert(key, vsa.clone());
+                    vsa
                 }
-            })
-        .for_each(|vsa| unifier.push(vsa));
-        },
+            };
 
-        (Lit::StringConst(s), Lit::StringConst(inp_str)) if inp_str.contains(s) => {
-            let re = regex(s);
-            let start = inp_str.find(s).unwrap();
-            let end = start + s.len();
-            // dbg!(s, start, end);
-            let start_lit = Lit::LocConst(start);
-            let end_lit = Lit::LocConst(end);
-            let start_vsa = learn_to_depth(inp, &start_lit, cache, bank, depth - 1);
-            let end_vsa = learn_to_depth(inp, &end_lit, cache, bank, depth - 1);
-            unifier.push(VSA::Join {
-                op: Fun::Slice,
-                children: vec![
-                    start_vsa,
-                    end_vsa,
-                ],
-                children_goals: vec![start_lit, end_lit],
+            res = Some(match res {
+                Some(res) => Arc::new(res.intersect_within(&vsa, self.budget)),
+                None => vsa,
             });
-        },
-
-        (Lit::StringConst(s), Lit::StringConst(inp_str)) if !inp_str.contains(s) && !s.contains(inp_str) => {
-            let set = (1..s.len())
-                .map(|i| VSA::Join {
-                    op: Fun::Concat,
-                    children: vec![
-                        learn_to_depth(
-                            inp,
-                            &Lit::StringConst(s[0..i].to_string()),
-                            cache,
-                            bank,
-                            depth - 1
-                        ),
-                        learn_to_depth(
-                            inp,
-                            &Lit::StringConst(s[i..].to_string()),
-                            cache,
-                            bank,
-                            depth - 1
-                        ),
-                    ],
-                    children_goals: vec![Lit::StringConst(s[0..i].to_string()), Lit::StringConst(s[i..].to_string())],
-                })
-            .map(Rc::new)
-                .collect();
+        }
+        res.unwrap()
+    }
 
-            unifier.push(VSA::Union(set));
+    fn witnesses(&self, out: &D::Lit) -> Vec<VSAOf<D>> {
+        let mut unifier = Vec::new();
+        if let Some(res) = self.cache.get(out) {
+            unifier.push(res.as_ref().clone());
         }
 
-    // TODO: figure out the index
-    // (Lit::LocConst(n), Lit::StringConst(s)) if s.chars().nth(*n).is_some_and(|ch| ch == ' ') => {
-    //     let lhs = Rc::new(VSA::singleton(AST::Lit(Lit::Input)));
-    //     let space = cache.get(&Lit::StringConst(" ".to_string())).unwrap().clone();
-    //     let wb = cache.get(&Lit::StringConst("\\b".to_string())).unwrap().clone();
-
-    //     unifier.push(VSA::Join {
-    //         op: Fun::Find,
-    //         children: vec![lhs.clone(), space],
-    //     });
-
-    //     if s.chars().nth(n - 1).is_some_and(|ch| ch.is_alphanumeric()) {
-    //         unifier.push(VSA::Join {
-    //             op: Fun::Find,
-    //             children: vec![lhs, wb],
-    //         });
-    //     }
-    // }
-    );
-
-    let res = unifier
-        .into_iter()
-        .map(Rc::new)
-        .fold(Rc::new(VSA::empty()), |acc, x| Rc::new(VSA::unify(acc, x)));
+        let ctx = WitnessCtx {
+            inp: self.inp,
+            bank: self.bank,
+        };
+        for witness in self.config.witnesses.enabled_without(self.disabled) {
+            unifier.extend(
+                witness
+                    .witness(&ctx, out)
+                    .into_iter()
+                    .filter_map(|vsa| self.without_disabled(vsa)),
+            );
+        }
 
-    match res.as_ref() {
-        VSA::Union(s) if s.is_empty() => todo!(), //bottom up?
-        _ => {}
+        unifier
     }
 
-    // cache.insert(out.clone(), res.clone());
-    res
+    // drops the alternatives of a witness's VSA whose root is a disabled
+    // operator, e.g. the Uppercase ones of the case witness when only
+    // Lowercase is left
+    fn without_disabled(&self, vsa: VSAOf<D>) -> Option<VSAOf<D>> {
+        match vsa {
+            vsa::VSA::Join { op, .. } if self.disabled.contains(&op) => None,
+            vsa::VSA::Union(vsas) => Some(vsa::VSA::Union(
+                vsas.into_iter()
+                    .filter_map(|vsa| {
+                        self.without_disabled(Arc::unwrap_or_clone(vsa))
+                            .map(Arc::new)
+                    })
+                    .collect(),
+            )),
+            vsa => Some(vsa),
+        }
+    }
 }
 
-pub fn bottom_up<'a>(
-    inps: impl Iterator<Item = &'a Lit> + Clone,
-    size: usize,
-    cache: &mut HashMap<Vec<Lit>, Rc<VSA>>,
-    bank: &mut Bank<AST>,
-    regex_bank: &mut Bank<AST>,
-    enable_bools: bool,
-) {
-    dbg!(size);
-    bank.grow_to(size);
-    regex_bank.grow_to(size);
-    // builds a VSA for a given I/O example
-    // then we can add these to the cache for `learn`
-
-    // TODO: a better way to keep track of size, make the bank store
-    // by size so that we can just directly make expressions of the correct size
-    //
-    // TODO: probably remove LocAdd and LocSub in favor for LocInc and LocDec or something
-    use vsa::{Fun::*, Lit::*};
-
-    #[rustfmt::skip]
-    let regexes_of_size = |n: usize| {
-        regex_bank.size(n).iter()
-    };
-
-    #[rustfmt::skip]
-    let strings_of_size = |n: usize| {
-        bank.size(n).iter().filter(|e| {
-            matches!(
-                e,
-                AST::Lit(Input | StringConst(_)) | AST::App { fun: Concat | Slice, .. }
-                | AST::JS { typ: vsa::Typ::Str, .. }
-            )
-        })
-    };
-
-    #[rustfmt::skip]
-    let locs_of_size = |n: usize| {
-        bank.size(n).iter().filter(|e| {
-            matches!(
-                e,
-                AST::Lit(LocConst(_) | LocEnd) | AST::App { fun: Find | LocAdd | LocSub, .. }
-            )
-        })
-    };
+// Enumerates programs bottom up starting from the size 1 primitives in `bank`,
+// returning every program that maps `inp` to `out`, or an empty VSA if there
+// aren't any up to FALLBACK_SIZE. If the budget runs out first the goal is
+// left unlearned. The operators, tokens and bools are the config's.
+pub fn learn_bottom_up<D: Dsl>(
+    inp: &D::Lit,
+    out: &D::Lit,
+    bank: &Bank<ASTOf<D>>,
+    config: &SynthConfig<D>,
+    budget: &Budget,
+) -> Arc<VSAOf<D>> {
+    let mut cache = HashMap::new();
+    let mut local_bank = Bank::new();
+    let mut token_bank = Bank::new();
+
+    let prims = bank.entries.first().cloned().unwrap_or_default();
+    for prim in prims {
+        let outs = vec![prim.eval(inp)];
+        let vsa = Arc::new(vsa::VSA::singleton(prim.clone()));
+        match cache.get(&outs) {
+            Some(old) => {
+                let unified = Arc::new(vsa::VSA::unify(Arc::clone(old), vsa));
+                cache.insert(outs, unified);
+            }
+            None => {
+                cache.insert(outs, vsa);
+            }
+        }
+        local_bank.size_mut(1).push(prim);
+    }
+    let example = [(inp.clone(), out.clone())];
+    for prim in config.with_examples(&config.tokens, &example, D::example_tokens) {
+        token_bank.size_mut(1).push(vsa::AST::Lit(prim));
+    }
 
-    #[rustfmt::skip]
-    let bools_of_size = |n: usize| {
-        bank.size(n).iter().filter(|e| {
-            matches!(
-                e,
-                AST::Lit(BoolConst(_)) | AST::App { fun: Equal, .. }
+    let goal = vec![out.clone()];
+    let enable_bools = config.enable_bools(&example);
+    for size in 1..=FALLBACK_SIZE {
+        let grown = if size > 1 {
+            bottom_up::<D>(
+                std::iter::once(inp),
+                size,
+                &mut cache,
+                &mut local_bank,
+                &mut token_bank,
+                &config.operators,
+                enable_bools,
+                budget,
             )
-        })
-    };
-
-    let adjs: Vec<AST> = {
-        let loc_adds = (1..size).flat_map(|i| {
-            let lhs_size = i;
-            let rhs_size = size - i;
-            // dbg!(locs_of_size(dbg!(lhs_size)).collect::<Vec<_>>());
-            iproduct!(locs_of_size(lhs_size), locs_of_size(rhs_size)).map(|(lhs, rhs)| AST::App {
-                fun: Fun::LocAdd,
-                args: vec![lhs.clone(), rhs.clone()],
-            })
-        });
+        } else {
+            Ok(())
+        };
 
-        let loc_subs = (1..size).flat_map(|i| {
-            let lhs_size = i;
-            let rhs_size = size - i;
-            iproduct!(locs_of_size(lhs_size), locs_of_size(rhs_size)).map(|(lhs, rhs)| AST::App {
-                fun: Fun::LocSub,
-                args: vec![lhs.clone(), rhs.clone()],
-            })
-        });
+        if let Some(vsa) = cache.get(&goal) {
+            return vsa.clone();
+        }
 
-        // I guess the concat witness function is complete
-        // so this isn't needed
-        let concats = (1..size).flat_map(|i| {
-            let lhs_size = i;
-            let rhs_size = size - i;
-            iproduct!(strings_of_size(lhs_size), strings_of_size(rhs_size)).map(|(lhs, rhs)| {
-                AST::App {
-                    fun: Fun::Concat,
-                    args: vec![lhs.clone(), rhs.clone()],
-                }
-            })
-        });
+        if grown.is_err() {
+            return Arc::new(vsa::VSA::Unlearned {
+                start: inp.clone(),
+                goal: out.clone(),
+                others: Vec::new(),
+            });
+        }
+    }
 
-        let re_concats = (1..size).flat_map(|i| {
-            let lhs_size = i;
-            let rhs_size = size - i;
-            iproduct!(regexes_of_size(lhs_size), regexes_of_size(rhs_size)).map(|(lhs, rhs)| {
-                AST::App {
-                    fun: Fun::Concat,
-                    args: vec![lhs.clone(), rhs.clone()],
-                }
-            })
-        });
+    Arc::new(vsa::VSA::empty())
+}
 
-        // let finds = (1..size - 1).flat_map(|i| {
-        //     let rhs_size = i;
-        //     let index_size = size - 1 - i;
-        //     iproduct!(
-        //         strings_of_size(rhs_size),
-        //         locs_of_size(index_size)
-        //     ).flat_map(|(rhs, index)| {
-        //         [
-        //             AST::App {
-        //                 fun: Fun::Find,
-        //                 args: vec![AST::Lit(Lit::Input), rhs.clone(), index.clone()],
-        //             },
-        //             AST::App {
-        //                 fun: Fun::FindEnd,
-        //                 args: vec![AST::Lit(Lit::Input), rhs.clone(), index.clone()],
-        //             },
-        //         ]
-        //     })
-        // });
-
-        let finds = (1..size - 1).flat_map(|l| {
-            (l + 1..size).flat_map(move |r| {
-                let lhs_size = l;
-                let rhs_size = r - l;
-                let index_size = size - r;
-                // dbg!(lhs_size, rhs_size, index_size);
-                iproduct!(
-                    strings_of_size(lhs_size),
-                    // strings_of_size(rhs_size),
-                    strings_of_size(rhs_size).chain(regexes_of_size(rhs_size)),
-                    locs_of_size(index_size)
-                )
-                .flat_map(|(lhs, rhs, index)| {
-                    [
-                        AST::App {
-                            fun: Fun::Find,
-                            args: vec![lhs.clone(), rhs.clone(), index.clone()],
-                        },
-                        AST::App {
-                            fun: Fun::FindEnd,
-                            args: vec![lhs.clone(), rhs.clone(), index.clone()],
-                        },
-                    ]
-                })
-            })
-        });
+// builds a VSA for a given I/O example
+// then we can add these to the cache for `learn`
+//
+// If the budget runs out part way through, the bank and cache keep the
+// programs of this size found so far
+#[allow(clippy::too_many_arguments)]
+pub fn bottom_up<'a, D: Dsl>(
+    inps: impl Iterator<Item = &'a D::Lit> + Clone,
+    size: usize,
+    cache: &mut HashMap<Vec<D::Lit>, Arc<VSAOf<D>>>,
+    bank: &mut Bank<ASTOf<D>>,
+    token_bank: &mut Bank<ASTOf<D>>,
+    operators: &[D::Fun],
+    enable_bools: bool,
+    budget: &Budget,
+) -> Result<(), Exhausted> {
+    bank.grow_to(size);
+    token_bank.grow_to(size);
 
-        let slices = (1..size).flat_map(|i| {
-            let lhs_size = i;
-            let rhs_size = size - i;
-            iproduct!(locs_of_size(lhs_size), locs_of_size(rhs_size)).map(|(lhs, rhs)| AST::App {
-                fun: Fun::Slice,
-                args: vec![lhs.clone(), rhs.clone()],
-            })
-        });
+    let entries = bank.total_entries();
+    let mut adjs = Vec::new();
+    let mut res = Ok(());
 
-        let re_groups = (1..size - 1).flat_map(|size| {
-            strings_of_size(size).map(|e| AST::App {
-                fun: Fun::Concat,
-                args: vec![e.clone(), AST::Lit(Lit::StringConst("+".to_string()))],
-            })
-        });
-        // dbg!(re_groups.clone().collect::<Vec<_>>());
-
-        let loc_eq_size = if enable_bools { size } else { 0 };
-        let loc_eqs = (1..loc_eq_size).flat_map(|i| {
-            let lhs_size = i;
-            let rhs_size = size - i;
-            iproduct!(locs_of_size(lhs_size), locs_of_size(rhs_size)).map(|(lhs, rhs)| AST::App {
-                fun: Fun::Equal,
-                args: vec![lhs.clone(), rhs.clone()],
-            })
-        });
+    // TODO: a better way to keep track of size, make the bank store
+    // by size so that we can just directly make expressions of the correct size
+    for adj in D::enumerate(bank, token_bank, size, operators, enable_bools) {
+        res = budget.check_bank(entries + adjs.len());
+        if res.is_err() {
+            break;
+        }
 
-        // loc_adds
-        //     .chain(loc_subs)
-        re_concats
-            // .chain(loc_adds)
-            // .chain(concats)
-            .chain(slices)
-            .chain(finds)
-            .chain(re_groups)
-            .chain(loc_eqs)
-    }
-    .filter(|adj| {
         let outs = inps.clone().map(|inp| adj.eval(inp)).collect::<Vec<_>>();
         use std::collections::hash_map::Entry;
 
-        // First Last, Another Name
-        // it was just a coincidence the last name has a in pos 2 :::
-        match adj {
-            AST::App { fun: Fun::Find | Fun::FindEnd, args } => {
-                match args.as_slice() {
-                    [AST::Lit(Lit::Input), AST::Lit(Lit::StringConst(s)), AST::Lit(Lit::LocConst(n))] => {
-                        if s == "[A-Z]" {
-                            dbg!(adj, &outs, cache.get(&outs));
-                        }
-                    }
-                    _ => {}
-                }
-            }
-            _ => {}
-        }
-
-        // dbg!(adj.size(), size);
-        // dbg!(adj.size(), size, bank.len());
         match cache.entry(outs) {
             Entry::Vacant(e) => {
-                e.insert(Rc::new(VSA::singleton(adj.clone())));
-                true
+                e.insert(Arc::new(vsa::VSA::singleton(adj.clone())));
+                adjs.push(adj);
             }
             Entry::Occupied(mut e) => {
                 let old = e.get_mut();
-                *old = Rc::new(VSA::unify(
+                *old = Arc::new(vsa::VSA::unify(
                     old.clone(),
-                    Rc::new(VSA::singleton(adj.clone())),
+                    Arc::new(vsa::VSA::singleton(adj)),
                 ));
-                false
             }
-            // _ => false,
         }
-        // if let Entry::Vacant(e) = cache.entry(outs) {
-        //     e.insert(Rc::new(VSA::singleton(adj.clone())));
-        //     true
-        // } else {
-        //     false
-        // }
-    })
-    .collect::<Vec<_>>();
+    }
 
     bank.size_mut(size).extend(adjs);
-    // dbg!(&bank);
+    res
 }
 
 pub fn top_down_vsa(examples: &[(Lit, Lit)]) -> (VSA, Option<AST>) {
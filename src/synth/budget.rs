@@ -0,0 +1,137 @@
+use std::{
+    cell::Cell,
+    fmt::Display,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Exhausted {
+    Deadline,
+    BankEntries,
+    VSANodes,
+    Cancelled,
+}
+
+impl Display for Exhausted {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Exhausted::Deadline => write!(f, "ran out of time"),
+            Exhausted::BankEntries => write!(f, "too many programs in the bank"),
+            Exhausted::VSANodes => write!(f, "too many VSA nodes"),
+            Exhausted::Cancelled => write!(f, "cancelled"),
+        }
+    }
+}
+
+// How much of the budget a run has used so far. Shared so that it can be
+// watched from another thread while synthesis runs.
+#[derive(Debug, Default)]
+pub struct Progress {
+    bank_entries: AtomicUsize,
+    vsa_nodes: AtomicUsize,
+}
+
+impl Progress {
+    pub fn bank_entries(&self) -> usize {
+        self.bank_entries.load(Ordering::Relaxed)
+    }
+
+    pub fn vsa_nodes(&self) -> usize {
+        self.vsa_nodes.load(Ordering::Relaxed)
+    }
+}
+
+// Limits on how much work synthesis can do. The checks are cooperative,
+// `bottom_up`, `learn` and `intersect` call into the budget as they go and
+// stop with what they have so far once it runs out. After that it stays
+// exhausted, so everything else using the same budget stops quickly too and
+// callers can find out why from `exhausted`.
+#[derive(Debug, Default)]
+pub struct Budget {
+    deadline: Option<Instant>,
+    max_bank_entries: Option<usize>,
+    max_vsa_nodes: Option<usize>,
+    cancelled: Arc<AtomicBool>,
+    progress: Arc<Progress>,
+    exhausted: Cell<Option<Exhausted>>,
+}
+
+impl Budget {
+    pub fn unlimited() -> Self {
+        Budget::default()
+    }
+
+    pub fn with_timeout(self, timeout: Duration) -> Self {
+        self.with_deadline(Instant::now() + timeout)
+    }
+
+    pub fn with_deadline(mut self, deadline: Instant) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    pub fn with_max_bank_entries(mut self, max: usize) -> Self {
+        self.max_bank_entries = Some(max);
+        self
+    }
+
+    pub fn with_max_vsa_nodes(mut self, max: usize) -> Self {
+        self.max_vsa_nodes = Some(max);
+        self
+    }
+
+    // setting the flag, e.g. from another thread, cancels synthesis
+    pub fn cancel_flag(&self) -> Arc<AtomicBool> {
+        self.cancelled.clone()
+    }
+
+    pub fn progress(&self) -> Arc<Progress> {
+        self.progress.clone()
+    }
+
+    pub fn exhausted(&self) -> Option<Exhausted> {
+        self.exhausted.get()
+    }
+
+    pub fn check(&self) -> Result<(), Exhausted> {
+        if let Some(reason) = self.exhausted.get() {
+            Err(reason)
+        } else if self.cancelled.load(Ordering::Relaxed) {
+            self.stop(Exhausted::Cancelled)
+        } else if self
+            .deadline
+            .is_some_and(|deadline| Instant::now() >= deadline)
+        {
+            self.stop(Exhausted::Deadline)
+        } else {
+            Ok(())
+        }
+    }
+
+    pub fn check_bank(&self, entries: usize) -> Result<(), Exhausted> {
+        self.check()?;
+        self.progress.bank_entries.store(entries, Ordering::Relaxed);
+        match self.max_bank_entries {
+            Some(max) if entries > max => self.stop(Exhausted::BankEntries),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn spend_vsa_nodes(&self, nodes: usize) -> Result<(), Exhausted> {
+        self.check()?;
+        let total = self.progress.vsa_nodes.fetch_add(nodes, Ordering::Relaxed) + nodes;
+        match self.max_vsa_nodes {
+            Some(max) if total > max => self.stop(Exhausted::VSANodes),
+            _ => Ok(()),
+        }
+    }
+
+    fn stop(&self, reason: Exhausted) -> Result<(), Exhausted> {
+        self.exhausted.set(Some(reason));
+        Err(reason)
+    }
+}
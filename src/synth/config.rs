@@ -0,0 +1,329 @@
+use std::{collections::HashMap, path::Path, sync::Arc, time::Duration};
+
+use serde::Deserialize;
+
+use super::bank::Bank;
+use super::budget::Budget;
+use super::dsl::{ASTOf, Dsl, StringDsl, VSAOf};
+use super::rank::Ranking;
+use super::token::{Class, Token};
+use super::vsa::{self, Fun, Lit, Typ};
+use super::witness::WitnessRegistry;
+use crate::task::TaskError;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Bools {
+    // only when some example has a bool output
+    Auto,
+    Always,
+    Never,
+}
+
+// a `Dsl` hook for the literals found in the examples
+type FromExamples<D> = fn(&[(<D as Dsl>::Lit, <D as Dsl>::Lit)]) -> Vec<<D as Dsl>::Lit>;
+
+// What bottom up enumeration starts from and how far it goes. Shared by
+// `top_down` and the Learn button in the GUI.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SynthConfig<D: Dsl> {
+    pub primitives: Vec<D::Lit>,
+    pub tokens: Vec<D::Lit>,
+    // also add the primitives and tokens the DSL picks out of the examples
+    pub example_primitives: bool,
+    pub max_size: usize,
+    pub operators: Vec<D::Fun>,
+    pub bools: Bools,
+    // limits for the `Budget` of a run, unlimited if None
+    pub timeout: Option<Duration>,
+    pub max_bank_entries: Option<usize>,
+    pub max_vsa_nodes: Option<usize>,
+    // picks the final program instead of the cheapest one if set
+    pub ranking: Option<Ranking>,
+    // what `learn` tries for each goal before falling back to enumerating
+    pub witnesses: WitnessRegistry<D>,
+}
+
+impl<D: Dsl> Default for SynthConfig<D> {
+    fn default() -> Self {
+        SynthConfig {
+            primitives: D::primitives(),
+            tokens: D::tokens(),
+            example_primitives: true,
+            max_size: 6,
+            operators: D::operators(),
+            bools: Bools::Auto,
+            timeout: None,
+            max_bank_entries: None,
+            max_vsa_nodes: None,
+            ranking: None,
+            witnesses: D::witnesses(),
+        }
+    }
+}
+
+impl<D: Dsl> SynthConfig<D> {
+    // a fresh budget with this config's limits, any timeout starts now
+    pub fn budget(&self) -> Budget {
+        let mut budget = Budget::unlimited();
+        if let Some(timeout) = self.timeout {
+            budget = budget.with_timeout(timeout);
+        }
+        if let Some(max) = self.max_bank_entries {
+            budget = budget.with_max_bank_entries(max);
+        }
+        if let Some(max) = self.max_vsa_nodes {
+            budget = budget.with_max_vsa_nodes(max);
+        }
+        budget
+    }
+
+    pub fn enable_bools(&self, examples: &[(D::Lit, D::Lit)]) -> bool {
+        match self.bools {
+            Bools::Auto => examples
+                .iter()
+                .any(|(_, out)| D::typ_of_lit(out) == Typ::Bool),
+            Bools::Always => true,
+            Bools::Never => false,
+        }
+    }
+
+    // the operators `D::operators` has that this config leaves out, witnesses
+    // don't build them either
+    pub fn disabled_operators(&self) -> Vec<D::Fun> {
+        D::operators()
+            .into_iter()
+            .filter(|op| !self.operators.contains(op))
+            .collect()
+    }
+
+    pub(crate) fn with_examples(
+        &self,
+        lits: &[D::Lit],
+        examples: &[(D::Lit, D::Lit)],
+        from_examples: FromExamples<D>,
+    ) -> Vec<D::Lit> {
+        let extra = if self.example_primitives {
+            from_examples(examples)
+        } else {
+            Vec::new()
+        };
+        lits.iter().cloned().chain(extra).collect()
+    }
+
+    // the size 1 bank, token bank and observational equivalence cache that
+    // `bottom_up` starts from when run over `num_inputs` inputs
+    #[allow(clippy::type_complexity)]
+    pub fn banks(
+        &self,
+        examples: &[(D::Lit, D::Lit)],
+        num_inputs: usize,
+    ) -> (
+        Bank<ASTOf<D>>,
+        Bank<ASTOf<D>>,
+        HashMap<Vec<D::Lit>, Arc<VSAOf<D>>>,
+    ) {
+        let mut bank = Bank::new();
+        let mut token_bank = Bank::new();
+        let mut cache = HashMap::new();
+
+        // TODO:
+        // a cache that is only applied to regexes
+        let inputs = D::input_primitives(examples);
+        for prim in self
+            .with_examples(&self.primitives, examples, D::example_primitives)
+            .into_iter()
+            .chain(inputs)
+        {
+            bank.size_mut(1).push(vsa::AST::Lit(prim.clone()));
+            cache.insert(
+                vec![prim.clone(); num_inputs],
+                Arc::new(vsa::VSA::singleton(vsa::AST::Lit(prim))),
+            );
+        }
+
+        for prim in self.with_examples(&self.tokens, examples, D::example_tokens) {
+            token_bank.size_mut(1).push(vsa::AST::Lit(prim));
+        }
+
+        (bank, token_bank, cache)
+    }
+}
+
+// A config file is JSON, every field is optional and defaults to
+// `SynthConfig::default()`:
+// {
+//     "primitives": ["", " ", ".", { "loc": 0 }, { "loc": 1 }, { "loc": "end" }],
+//     "tokens": ["digits", "alpha", "upper", "lower", "whitespace", "punctuation", "start",
+//                "end"],
+//     "example_primitives": true,
+//     "max_size": 6,
+//     "operators": ["Concat", "Find", "FindEnd", "Slice", "Uppercase", "Lowercase", "Equal",
+//                   "If"],
+//     "bools": "auto",
+//     "timeout": 10.0,
+//     "max_bank_entries": 100000,
+//     "max_vsa_nodes": 1000000,
+//     "ranking": { "weights": { "size": 1.0, "input_coverage": -2.0 } },
+//     "witnesses": ["const", "loc_end", "equal", "concat_input", "slice", "column", "case",
+//                   "concat_map", "format_int", "parse_int", "arith", "concat_split"]
+// }
+//
+// The inputs are always primitives, so they aren't listed. Plain numbers are
+// integers, locations are written as objects. Tokens are character classes by
+// name, other tokens are objects like { "literal": "-" } or
+// { "regex": "[A-Z]{2}" }, and a regex that doesn't compile is an error. The
+// timeout is in seconds, and like the other limits there is none by default.
+// Without a ranking the cheapest program wins, see `rank.rs` for its format.
+// Only the listed witnesses are tried, in that order.
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub(crate) struct ConfigRepr {
+    primitives: Option<Vec<PrimRepr>>,
+    tokens: Option<Vec<TokenRepr>>,
+    example_primitives: Option<bool>,
+    max_size: Option<usize>,
+    operators: Option<Vec<Fun>>,
+    bools: Option<Bools>,
+    timeout: Option<f64>,
+    max_bank_entries: Option<usize>,
+    max_vsa_nodes: Option<usize>,
+    ranking: Option<Ranking>,
+    witnesses: Option<Vec<String>>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum PrimRepr {
+    Str(String),
+    Bool(bool),
+    Int(i64),
+    Loc { loc: LocRepr },
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum LocRepr {
+    Index(usize),
+    Named(String),
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum TokenRepr {
+    Class(String),
+    Literal { literal: String },
+    Regex { regex: String },
+}
+
+impl TryFrom<TokenRepr> for Lit {
+    type Error = TaskError;
+
+    fn try_from(token: TokenRepr) -> Result<Self, Self::Error> {
+        let token = match token {
+            TokenRepr::Class(name) => Token::Class(
+                Class::from_name(&name)
+                    .ok_or_else(|| TaskError::Format(format!("unknown token class {name:?}")))?,
+            ),
+            TokenRepr::Literal { literal } => Token::Literal(literal),
+            TokenRepr::Regex { regex } => Token::regex(&regex)
+                .map_err(|e| TaskError::Format(format!("invalid token regex {regex:?}: {e}")))?,
+        };
+        Ok(Lit::Token(token))
+    }
+}
+
+impl TryFrom<PrimRepr> for Lit {
+    type Error = TaskError;
+
+    fn try_from(prim: PrimRepr) -> Result<Self, Self::Error> {
+        match prim {
+            PrimRepr::Str(s) => Ok(Lit::StringConst(s)),
+            PrimRepr::Bool(b) => Ok(Lit::BoolConst(b)),
+            PrimRepr::Int(n) => Ok(Lit::IntConst(n)),
+            PrimRepr::Loc {
+                loc: LocRepr::Index(n),
+            } => Ok(Lit::LocConst(n)),
+            PrimRepr::Loc {
+                loc: LocRepr::Named(name),
+            } if name == "end" => Ok(Lit::LocEnd),
+            PrimRepr::Loc {
+                loc: LocRepr::Named(name),
+            } => Err(TaskError::Format(format!("unknown location {name:?}"))),
+        }
+    }
+}
+
+impl TryFrom<ConfigRepr> for SynthConfig<StringDsl> {
+    type Error = TaskError;
+
+    fn try_from(repr: ConfigRepr) -> Result<Self, Self::Error> {
+        let mut config = SynthConfig::default();
+
+        if let Some(prims) = repr.primitives {
+            config.primitives = std::iter::once(Ok(Lit::Input(0)))
+                .chain(prims.into_iter().map(Lit::try_from))
+                .collect::<Result<_, _>>()?;
+        }
+        if let Some(tokens) = repr.tokens {
+            config.tokens = tokens
+                .into_iter()
+                .map(Lit::try_from)
+                .collect::<Result<_, _>>()?;
+        }
+        if let Some(example_primitives) = repr.example_primitives {
+            config.example_primitives = example_primitives;
+        }
+        if let Some(max_size) = repr.max_size {
+            config.max_size = max_size;
+        }
+        if let Some(operators) = repr.operators {
+            config.operators = operators;
+        }
+        if let Some(bools) = repr.bools {
+            config.bools = bools;
+        }
+        if let Some(timeout) = repr.timeout {
+            config.timeout = Some(
+                Duration::try_from_secs_f64(timeout)
+                    .map_err(|_| TaskError::Format(format!("invalid timeout {timeout}")))?,
+            );
+        }
+        config.max_bank_entries = repr.max_bank_entries;
+        config.max_vsa_nodes = repr.max_vsa_nodes;
+        config.ranking = repr.ranking;
+        if let Some(names) = repr.witnesses {
+            let all = config
+                .witnesses
+                .names()
+                .map(|(name, _)| name)
+                .collect::<Vec<_>>();
+            for name in all {
+                config.witnesses.set_enabled(name, false);
+            }
+            for (i, name) in names.iter().enumerate() {
+                if !config.witnesses.move_to(name, i) {
+                    return Err(TaskError::Format(format!("unknown witness {name:?}")));
+                }
+                config.witnesses.set_enabled(name, true);
+            }
+        }
+
+        if config.max_size == 0 {
+            Err(TaskError::Format("max_size must be at least 1".to_string()))
+        } else {
+            Ok(config)
+        }
+    }
+}
+
+impl SynthConfig<StringDsl> {
+    pub fn from_json(src: &str) -> Result<Self, TaskError> {
+        let repr: ConfigRepr = serde_json::from_str(src)?;
+        repr.try_into()
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, TaskError> {
+        SynthConfig::from_json(&std::fs::read_to_string(path)?)
+    }
+}
@@ -0,0 +1,71 @@
+use std::sync::Arc;
+
+use super::dsl::{ASTOf, Dsl, VSAOf};
+
+// An input the programs in a VSA don't agree on, to ask the user about
+#[derive(Debug, Clone)]
+pub struct Question<D: Dsl> {
+    pub input: D::Lit,
+    // cheapest first
+    pub answers: Vec<Answer<D>>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Answer<D: Dsl> {
+    pub output: D::Lit,
+    // the cheapest program with this output
+    pub program: ASTOf<D>,
+    pub cost: usize,
+    // every program with this output, what's left once the user picks it
+    pub vsa: Arc<VSAOf<D>>,
+}
+
+// Looks for an input that splits the programs in the VSA into groups with
+// different outputs, trying the unlabeled `other_inputs` before inputs made
+// up by mutating the examples' inputs. Out of the inputs the programs
+// disagree on, the one where the best program with a different output than
+// the best overall is cheapest wins, since that's where the top candidates
+// really compete.
+pub fn distinguishing_input<D: Dsl>(
+    vsa: &VSAOf<D>,
+    example_inputs: &[D::Lit],
+    other_inputs: &[D::Lit],
+) -> Option<Question<D>> {
+    let mutated = example_inputs.iter().flat_map(D::mutate_input);
+
+    let mut best: Option<(usize, Question<D>)> = None;
+    let mut tried = Vec::new();
+    for input in other_inputs.iter().cloned().chain(mutated) {
+        if example_inputs.contains(&input) || tried.contains(&input) {
+            continue;
+        }
+        tried.push(input.clone());
+
+        let mut answers = vsa
+            .cluster(&input)
+            .into_iter()
+            // programs with unlearned parts can't be compared yet
+            .filter_map(|(output, vsa)| {
+                let output = output?;
+                let (cost, program) = vsa.pick_best()?;
+                Some(Answer {
+                    output,
+                    program,
+                    cost,
+                    vsa,
+                })
+            })
+            .collect::<Vec<_>>();
+        if answers.len() < 2 {
+            continue;
+        }
+        answers.sort_by_key(|answer| answer.cost);
+
+        let runner_up = answers[1].cost;
+        if best.as_ref().is_none_or(|(cost, _)| runner_up < *cost) {
+            best = Some((runner_up, Question { input, answers }));
+        }
+    }
+
+    best.map(|(_, question)| question)
+}
@@ -0,0 +1,591 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Debug,
+    hash::Hash,
+    sync::Arc,
+};
+
+use itertools::{iproduct, Itertools};
+
+use super::bank::Bank;
+use super::budget::Budget;
+use super::token::{Class, Token};
+use super::vsa::{self, Cost, Fun, InputLit, Language, Lit, Typ};
+use super::witness::{self, WitnessRegistry};
+use super::AST;
+
+pub type VSAOf<D> = vsa::VSA<<D as Dsl>::Lit, <D as Dsl>::Fun>;
+pub type ASTOf<D> = vsa::AST<<D as Dsl>::Lit, <D as Dsl>::Fun>;
+
+// Everything `top_down`, `bottom_up` and `learn` need to know about a language
+pub trait Dsl: Sized + 'static {
+    // programs are ranked by the cost of their literals and functions
+    type Lit: Clone + Eq + Hash + Debug + InputLit + Cost;
+    type Fun: Language<Self::Lit> + Copy + Eq + Hash + Debug + Cost;
+
+    // the default size 1 programs bottom up enumeration starts from
+    fn primitives() -> Vec<Self::Lit>;
+
+    // size 1 programs that are only used as arguments, e.g. regexes
+    fn tokens() -> Vec<Self::Lit> {
+        Vec::new()
+    }
+
+    // extra primitives picked out of the examples
+    fn example_primitives(_examples: &[(Self::Lit, Self::Lit)]) -> Vec<Self::Lit> {
+        Vec::new()
+    }
+
+    // extra tokens picked out of the examples
+    fn example_tokens(_examples: &[(Self::Lit, Self::Lit)]) -> Vec<Self::Lit> {
+        Vec::new()
+    }
+
+    // the inputs past the first, e.g. one per column, always primitives
+    fn input_primitives(_examples: &[(Self::Lit, Self::Lit)]) -> Vec<Self::Lit> {
+        Vec::new()
+    }
+
+    // inputs a bit different from an example's, to look for ones the
+    // programs learned so far disagree on
+    fn mutate_input(_inp: &Self::Lit) -> Vec<Self::Lit> {
+        Vec::new()
+    }
+
+    // the operators `enumerate` can build programs from
+    fn operators() -> Vec<Self::Fun>;
+
+    // The `if cond then else` operator, with the value of `cond` that picks
+    // `then`. `top_down` uses it to split the examples when no single
+    // program fits all of them.
+    fn conditional() -> Option<(Self::Fun, Self::Lit)> {
+        None
+    }
+
+    // Conditions for a conditional built from the programs in the bottom up
+    // cache, keyed by their outputs on `inputs`. Each one has to pick `then`
+    // on the examples marked Some(true) and `else` on the ones marked
+    // Some(false), the others don't matter. Stops early once the budget runs
+    // out.
+    fn predicates(
+        _inputs: &[Self::Lit],
+        _cache: &HashMap<Vec<Self::Lit>, Arc<VSAOf<Self>>>,
+        _targets: &[Option<bool>],
+        _budget: &Budget,
+    ) -> Vec<VSAOf<Self>> {
+        Vec::new()
+    }
+
+    fn typ_of_lit(lit: &Self::Lit) -> Typ;
+
+    // the type of value a program evaluates to
+    fn typ(ast: &ASTOf<Self>) -> Typ;
+
+    // every program of exactly `size` built from the smaller programs in the
+    // banks, using only the given operators. Lazy so that `bottom_up` can stop
+    // part way through.
+    fn enumerate<'a>(
+        bank: &'a Bank<ASTOf<Self>>,
+        tokens: &'a Bank<ASTOf<Self>>,
+        size: usize,
+        operators: &'a [Self::Fun],
+        enable_bools: bool,
+    ) -> Box<dyn Iterator<Item = ASTOf<Self>> + 'a>;
+
+    fn witnesses() -> WitnessRegistry<Self>;
+
+    // named features of a program for a `Ranking` to weigh, see `rank.rs`
+    fn features(
+        ast: &ASTOf<Self>,
+        _examples: &[(Self::Lit, Self::Lit)],
+    ) -> Vec<(&'static str, f64)> {
+        vec![("size", ast.size() as f64), ("depth", ast.depth() as f64)]
+    }
+}
+
+pub fn of_typ<D: Dsl>(
+    bank: &Bank<ASTOf<D>>,
+    size: usize,
+    typ: Typ,
+) -> impl Iterator<Item = &ASTOf<D>> + Clone {
+    bank.size(size).iter().filter(move |e| D::typ(e) == typ)
+}
+
+// FlashFill style string transformations over `Lit` and `Fun`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StringDsl;
+
+impl Dsl for StringDsl {
+    type Lit = Lit;
+    type Fun = Fun;
+
+    fn primitives() -> Vec<Lit> {
+        vec![
+            Lit::Input(0),
+            Lit::StringConst("".to_string()),
+            Lit::StringConst(" ".to_string()),
+            Lit::StringConst(".".to_string()),
+            Lit::LocConst(0),
+            Lit::LocConst(1),
+            Lit::LocEnd,
+        ]
+    }
+
+    fn tokens() -> Vec<Lit> {
+        Class::ALL
+            .into_iter()
+            .map(|class| Lit::Token(Token::Class(class)))
+            .collect()
+    }
+
+    fn example_primitives(examples: &[(Lit, Lit)]) -> Vec<Lit> {
+        shared_chars(examples)
+            .into_iter()
+            .map(|c| Lit::StringConst(c.to_string()))
+            .collect()
+    }
+
+    fn example_tokens(examples: &[(Lit, Lit)]) -> Vec<Lit> {
+        shared_chars(examples)
+            .into_iter()
+            .map(|c| Lit::Token(Token::Literal(c.to_string())))
+            .collect()
+    }
+
+    fn input_primitives(examples: &[(Lit, Lit)]) -> Vec<Lit> {
+        let columns = examples
+            .iter()
+            .map(|(inp, _)| match inp {
+                Lit::Tuple(cols) => cols.len(),
+                _ => 1,
+            })
+            .max()
+            .unwrap_or(1);
+        (1..columns).map(Lit::Input).collect()
+    }
+
+    fn mutate_input(inp: &Lit) -> Vec<Lit> {
+        match inp {
+            Lit::StringConst(s) => mutations(s).into_iter().map(Lit::StringConst).collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    fn operators() -> Vec<Fun> {
+        vec![
+            Fun::Concat,
+            Fun::Find,
+            Fun::FindEnd,
+            Fun::Slice,
+            Fun::Uppercase,
+            Fun::Lowercase,
+            Fun::Equal,
+            Fun::If,
+        ]
+    }
+
+    fn conditional() -> Option<(Fun, Lit)> {
+        Some((Fun::If, Lit::BoolConst(true)))
+    }
+
+    // bools in the cache, and locations that are equal exactly when they
+    // should be, e.g. a Find that only fails on one kind of input
+    fn predicates(
+        inputs: &[Lit],
+        cache: &HashMap<Vec<Lit>, Arc<vsa::VSA<Lit, Fun>>>,
+        targets: &[Option<bool>],
+        budget: &Budget,
+    ) -> Vec<vsa::VSA<Lit, Fun>> {
+        let fits = |outs: &[Lit]| {
+            outs.iter()
+                .zip(targets)
+                .all(|(out, target)| target.is_none_or(|t| *out == Lit::BoolConst(t)))
+        };
+        let of_typ = |typ: Typ| {
+            cache
+                .iter()
+                .filter(move |(outs, _)| outs.iter().all(|out| Self::typ_of_lit(out) == typ))
+        };
+
+        // every pair is checked against the deadline, but only the
+        // predicates that are kept cost nodes
+        let equals = of_typ(Typ::Loc)
+            .tuple_combinations()
+            .take_while(|_| budget.check().is_ok())
+            .filter_map(|((l_outs, l), (r_outs, r))| {
+                let outs = inputs
+                    .iter()
+                    .zip(l_outs.iter().zip(r_outs))
+                    .map(|(inp, (l, r))| Fun::Equal.eval(&[l.clone(), r.clone()], inp))
+                    .collect::<Vec<_>>();
+                fits(&outs).then(|| vsa::VSA::Join {
+                    op: Fun::Equal,
+                    children: vec![l.clone(), r.clone()],
+                    children_goals: vec![l_outs[0].clone(), r_outs[0].clone()],
+                })
+            });
+        of_typ(Typ::Bool)
+            .filter(|(outs, _)| fits(outs))
+            .map(|(_, vsa)| vsa.as_ref().clone())
+            .chain(equals)
+            .take_while(|_| budget.spend_vsa_nodes(1).is_ok())
+            .collect()
+    }
+
+    fn typ_of_lit(lit: &Lit) -> Typ {
+        match lit {
+            Lit::StringConst(_) | Lit::Input(_) => Typ::Str,
+            Lit::LocConst(_) | Lit::LocEnd => Typ::Loc,
+            Lit::BoolConst(_) => Typ::Bool,
+            Lit::IntConst(_) => Typ::Int,
+            Lit::Tuple(_) => Typ::Tuple,
+            Lit::Token(_) => Typ::Token,
+        }
+    }
+
+    fn typ(ast: &AST) -> Typ {
+        match ast {
+            AST::Lit(l) => Self::typ_of_lit(l),
+            // tokens are concatenated into longer tokens
+            AST::App {
+                fun: Fun::Concat,
+                args,
+            } if Self::typ(&args[0]) == Typ::Token => Typ::Token,
+            AST::App { fun, .. } => match fun {
+                Fun::Concat
+                | Fun::Slice
+                | Fun::Lowercase
+                | Fun::Uppercase
+                | Fun::ConcatMap
+                | Fun::Apply => Typ::Str,
+                Fun::Find | Fun::FindEnd | Fun::LocAdd | Fun::LocSub => Typ::Loc,
+                Fun::Equal => Typ::Bool,
+                Fun::FormatInt | Fun::If => Typ::Str,
+                Fun::ParseInt | Fun::Add | Fun::Sub | Fun::Mul | Fun::Div => Typ::Int,
+            },
+            AST::JS { typ, .. } => typ.clone(),
+        }
+    }
+
+    fn enumerate<'a>(
+        bank: &'a Bank<AST>,
+        tokens: &'a Bank<AST>,
+        size: usize,
+        operators: &'a [Fun],
+        enable_bools: bool,
+    ) -> Box<dyn Iterator<Item = AST> + 'a> {
+        // TODO: probably remove LocAdd and LocSub in favor for LocInc and LocDec or something
+        #[rustfmt::skip]
+        let regexes_of_size = move |n: usize| {
+            tokens.size(n).iter()
+        };
+        let patterns_of_size =
+            move |n: usize| regexes_of_size(n).chain(of_typ::<Self>(bank, n, Typ::Token));
+
+        let strings_of_size = move |n: usize| of_typ::<Self>(bank, n, Typ::Str);
+        let locs_of_size = move |n: usize| of_typ::<Self>(bank, n, Typ::Loc);
+
+        Box::new({
+            let loc_adds = (1..size).flat_map(move |i| {
+                let lhs_size = i;
+                let rhs_size = size - i;
+                // dbg!(locs_of_size(dbg!(lhs_size)).collect::<Vec<_>>());
+                iproduct!(locs_of_size(lhs_size), locs_of_size(rhs_size)).map(|(lhs, rhs)| {
+                    AST::App {
+                        fun: Fun::LocAdd,
+                        args: vec![lhs.clone(), rhs.clone()],
+                    }
+                })
+            });
+
+            let loc_subs = (1..size).flat_map(move |i| {
+                let lhs_size = i;
+                let rhs_size = size - i;
+                iproduct!(locs_of_size(lhs_size), locs_of_size(rhs_size)).map(|(lhs, rhs)| {
+                    AST::App {
+                        fun: Fun::LocSub,
+                        args: vec![lhs.clone(), rhs.clone()],
+                    }
+                })
+            });
+
+            // I guess the concat witness function is complete
+            // so this isn't needed
+            let concats = (1..size).flat_map(move |i| {
+                let lhs_size = i;
+                let rhs_size = size - i;
+                iproduct!(strings_of_size(lhs_size), strings_of_size(rhs_size)).map(|(lhs, rhs)| {
+                    AST::App {
+                        fun: Fun::Concat,
+                        args: vec![lhs.clone(), rhs.clone()],
+                    }
+                })
+            });
+
+            // disabled operators get an empty range of sizes
+            let size_if = |fun: Fun| if operators.contains(&fun) { size } else { 0 };
+            let concat_size = size_if(Fun::Concat);
+            let find_size = size_if(Fun::Find).max(size_if(Fun::FindEnd));
+            let slice_size = size_if(Fun::Slice);
+
+            let re_concats = (1..concat_size).flat_map(move |i| {
+                let lhs_size = i;
+                let rhs_size = size - i;
+                iproduct!(regexes_of_size(lhs_size), regexes_of_size(rhs_size))
+                    // tokens too big to compile together
+                    .filter(|(lhs, rhs)| match (lhs, rhs) {
+                        (AST::Lit(Lit::Token(lhs)), AST::Lit(Lit::Token(rhs))) => {
+                            lhs.then(rhs).is_ok()
+                        }
+                        _ => true,
+                    })
+                    .map(|(lhs, rhs)| AST::App {
+                        fun: Fun::Concat,
+                        args: vec![lhs.clone(), rhs.clone()],
+                    })
+            });
+
+            // let finds = (1..size - 1).flat_map(|i| {
+            //     let rhs_size = i;
+            //     let index_size = size - 1 - i;
+            //     iproduct!(
+            //         strings_of_size(rhs_size),
+            //         locs_of_size(index_size)
+            //     ).flat_map(|(rhs, index)| {
+            //         [
+            //             AST::App {
+            //                 fun: Fun::Find,
+            //                 args: vec![AST::Lit(Lit::Input), rhs.clone(), index.clone()],
+            //             },
+            //             AST::App {
+            //                 fun: Fun::FindEnd,
+            //                 args: vec![AST::Lit(Lit::Input), rhs.clone(), index.clone()],
+            //             },
+            //         ]
+            //     })
+            // });
+
+            let finds = (1..find_size.saturating_sub(1))
+                .flat_map(move |l| {
+                    (l + 1..size).flat_map(move |r| {
+                        let lhs_size = l;
+                        let rhs_size = r - l;
+                        let index_size = size - r;
+                        // dbg!(lhs_size, rhs_size, index_size);
+                        iproduct!(
+                            strings_of_size(lhs_size),
+                            patterns_of_size(rhs_size),
+                            locs_of_size(index_size)
+                        )
+                        .flat_map(|(lhs, rhs, index)| {
+                            [
+                                AST::App {
+                                    fun: Fun::Find,
+                                    args: vec![lhs.clone(), rhs.clone(), index.clone()],
+                                },
+                                AST::App {
+                                    fun: Fun::FindEnd,
+                                    args: vec![lhs.clone(), rhs.clone(), index.clone()],
+                                },
+                            ]
+                        })
+                    })
+                })
+                .filter(move |ast| matches!(ast, AST::App { fun, .. } if operators.contains(fun)));
+
+            let slices = (1..slice_size).flat_map(move |i| {
+                let lhs_size = i;
+                let rhs_size = size - i;
+                iproduct!(locs_of_size(lhs_size), locs_of_size(rhs_size)).map(|(lhs, rhs)| {
+                    AST::App {
+                        fun: Fun::Slice,
+                        args: vec![lhs.clone(), rhs.clone()],
+                    }
+                })
+            });
+
+            let cases = [Fun::Uppercase, Fun::Lowercase]
+                .into_iter()
+                .filter(move |fun| size > 1 && operators.contains(fun))
+                .flat_map(move |fun| {
+                    strings_of_size(size - 1).map(move |e| AST::App {
+                        fun,
+                        args: vec![e.clone()],
+                    })
+                });
+
+            let loc_eq_size = if enable_bools { size_if(Fun::Equal) } else { 0 };
+            let loc_eqs = (1..loc_eq_size).flat_map(move |i| {
+                let lhs_size = i;
+                let rhs_size = size - i;
+                iproduct!(locs_of_size(lhs_size), locs_of_size(rhs_size)).map(|(lhs, rhs)| {
+                    AST::App {
+                        fun: Fun::Equal,
+                        args: vec![lhs.clone(), rhs.clone()],
+                    }
+                })
+            });
+
+            // loc_adds
+            //     .chain(loc_subs)
+            re_concats
+                // .chain(loc_adds)
+                // .chain(concats)
+                .chain(slices)
+                .chain(finds)
+                .chain(cases)
+                .chain(loc_eqs)
+        })
+    }
+
+    fn witnesses() -> WitnessRegistry<Self> {
+        WitnessRegistry::new()
+            .with(witness::ConstWitness)
+            .with(witness::LocEndWitness)
+            .with(witness::EqualWitness)
+            .with(witness::ConcatInputWitness)
+            .with(witness::SliceWitness)
+            .with(witness::ColumnWitness)
+            .with(witness::CaseWitness)
+            .with(witness::ConcatMapWitness)
+            .with(witness::FormatIntWitness)
+            .with(witness::ParseIntWitness)
+            .with(witness::ArithWitness)
+            .with(witness::ConcatSplitWitness)
+    }
+
+    fn features(ast: &AST, examples: &[(Lit, Lit)]) -> Vec<(&'static str, f64)> {
+        let mut counts = Counts::default();
+        counts.add(ast);
+
+        // how much of the outputs comes from the input rather than constants
+        let coverage = examples
+            .iter()
+            .map(|(_, out)| match out {
+                Lit::StringConst(out) if !out.is_empty() => {
+                    let len = out.chars().count() as f64;
+                    (1.0 - counts.output_const_len as f64 / len).max(0.0)
+                }
+                _ => 1.0,
+            })
+            .sum::<f64>()
+            / examples.len().max(1) as f64;
+
+        vec![
+            ("size", ast.size() as f64),
+            ("depth", ast.depth() as f64),
+            ("concats", counts.concats as f64),
+            ("consts", counts.consts as f64),
+            ("const_len", counts.const_len as f64),
+            ("tokens", counts.tokens as f64),
+            ("inputs", counts.inputs as f64),
+            ("locs", counts.locs as f64),
+            ("input_coverage", coverage),
+        ]
+    }
+}
+
+// what `StringDsl::features` counts in a program
+#[derive(Default)]
+struct Counts {
+    concats: usize,
+    consts: usize,
+    const_len: usize,
+    // only the constants that end up in the output, not find patterns
+    output_const_len: usize,
+    tokens: usize,
+    inputs: usize,
+    locs: usize,
+}
+
+impl Counts {
+    fn add(&mut self, ast: &AST) {
+        match ast {
+            AST::Lit(Lit::StringConst(s)) => {
+                let len = s.chars().count();
+                self.consts += 1;
+                self.const_len += len;
+                self.output_const_len += len;
+            }
+            AST::Lit(Lit::Token(Token::Literal(s))) => {
+                self.consts += 1;
+                self.const_len += s.chars().count();
+            }
+            AST::Lit(Lit::Token(_)) => self.tokens += 1,
+            AST::Lit(Lit::Input(_)) => self.inputs += 1,
+            AST::Lit(Lit::LocConst(_) | Lit::LocEnd) => self.locs += 1,
+            AST::Lit(Lit::IntConst(n)) => {
+                let len = n.to_string().len();
+                self.consts += 1;
+                self.const_len += len;
+                self.output_const_len += len;
+            }
+            AST::Lit(Lit::BoolConst(_) | Lit::Tuple(_)) => {}
+            AST::App { fun, args } => {
+                if *fun == Fun::Concat {
+                    self.concats += 1;
+                }
+                for arg in args {
+                    self.add(arg);
+                }
+            }
+            AST::JS { input, .. } => self.add(input),
+        }
+    }
+}
+
+// a word dropped or repeated, longer numbers and different case
+fn mutations(s: &str) -> Vec<String> {
+    let mut mutated = Vec::new();
+
+    let words = s.split(' ').collect::<Vec<_>>();
+    for i in 0..words.len() {
+        if words.len() > 1 {
+            let mut dropped = words.clone();
+            dropped.remove(i);
+            mutated.push(dropped.join(" "));
+        }
+        let mut repeated = words.clone();
+        repeated.insert(i, words[i]);
+        mutated.push(repeated.join(" "));
+    }
+
+    let mut longer = String::new();
+    let mut chars = s.chars().peekable();
+    while let Some(c) = chars.next() {
+        longer.push(c);
+        if c.is_ascii_digit() && !chars.peek().is_some_and(char::is_ascii_digit) {
+            longer.push('0');
+        }
+    }
+    mutated.push(longer);
+
+    mutated.push(s.to_lowercase());
+    mutated.push(s.to_uppercase());
+
+    mutated.retain(|m| m != s && !m.is_empty());
+    mutated
+}
+
+// the non alphanumeric chars that appear in every example, usually separators
+fn shared_chars(examples: &[(Lit, Lit)]) -> Vec<char> {
+    let mut char_sets = examples.iter().map(|(inp, out)| match (inp, out) {
+        (Lit::StringConst(_) | Lit::Tuple(_), Lit::StringConst(out)) => witness::columns(inp)
+            .into_iter()
+            .flat_map(|(_, col)| col.chars())
+            .chain(out.chars())
+            .filter(|c| !c.is_alphanumeric())
+            .collect::<HashSet<_>>(),
+        _ => HashSet::new(),
+    });
+
+    char_sets
+        .next()
+        .map(|s1| {
+            s1.iter()
+                .filter(|c| char_sets.clone().all(|s2| s2.contains(c)))
+                .cloned()
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default()
+}
@@ -0,0 +1,190 @@
+use std::{collections::BTreeMap, path::Path, sync::Arc};
+
+use serde::{Deserialize, Serialize};
+
+use super::config::SynthConfig;
+use super::dsl::{ASTOf, Dsl, VSAOf};
+use super::top_down_observed;
+use super::vsa::VSA;
+use crate::task::TaskError;
+
+// A linear model over the features `Dsl::features` picks out of a program,
+// programs with a lower score rank first. `Cost` is still used to enumerate
+// programs, the model reranks the cheapest `candidates` of them.
+//
+// A weights file is JSON, missing weights are 0:
+// {
+//     "weights": { "size": 1.0, "consts": 2.0, "input_coverage": -4.0 },
+//     "candidates": 100
+// }
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Ranking {
+    pub weights: BTreeMap<String, f64>,
+    pub candidates: usize,
+}
+
+impl Default for Ranking {
+    // close to ranking by `Cost`, but preferring programs that use the input
+    fn default() -> Self {
+        let weights = [
+            ("size", 1.0),
+            ("concats", 1.0),
+            ("consts", 1.0),
+            ("const_len", 0.1),
+            ("input_coverage", -2.0),
+        ];
+        Ranking {
+            weights: weights
+                .into_iter()
+                .map(|(name, weight)| (name.to_string(), weight))
+                .collect(),
+            candidates: 100,
+        }
+    }
+}
+
+// How `Ranking::train` fits the weights
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Training {
+    pub epochs: usize,
+    pub learning_rate: f64,
+    // how much better the right program should score than a wrong one
+    pub margin: f64,
+}
+
+impl Default for Training {
+    fn default() -> Self {
+        Training {
+            epochs: 20,
+            learning_rate: 0.1,
+            margin: 1.0,
+        }
+    }
+}
+
+type Features = Vec<(&'static str, f64)>;
+
+impl Ranking {
+    pub fn from_json(src: &str) -> Result<Self, TaskError> {
+        Ok(serde_json::from_str(src)?)
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, TaskError> {
+        Ranking::from_json(&std::fs::read_to_string(path)?)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), TaskError> {
+        Ok(std::fs::write(path, serde_json::to_string_pretty(self)?)?)
+    }
+
+    fn score_features(&self, features: &Features) -> f64 {
+        features
+            .iter()
+            .map(|(name, value)| self.weights.get(*name).unwrap_or(&0.0) * value)
+            .sum()
+    }
+
+    pub fn score<D: Dsl>(&self, ast: &ASTOf<D>, examples: &[(D::Lit, D::Lit)]) -> f64 {
+        self.score_features(&D::features(ast, examples))
+    }
+
+    // the best scoring of the cheapest `candidates` programs in the VSA
+    pub fn pick_best<D: Dsl>(
+        &self,
+        vsa: &Arc<VSAOf<D>>,
+        examples: &[(D::Lit, D::Lit)],
+    ) -> Option<(f64, ASTOf<D>)> {
+        VSA::ranked(vsa)
+            .take(self.candidates.max(1))
+            .map(|ast| (self.score::<D>(&ast, examples), ast))
+            .min_by(|(l, _), (r, _)| l.total_cmp(r))
+    }
+
+    // Fits the weights to a corpus of solved tasks, each a list of examples.
+    // For every task the programs that fit the first example are split into
+    // the ones that get the other examples right and the ones that don't,
+    // then the weights are nudged until the best right program scores better
+    // than every wrong one, by a margin. Returns how many tasks have both
+    // kinds of programs to train on, and how many of those the model ranks a
+    // right program first for after each epoch.
+    pub fn train<D: Dsl>(
+        &mut self,
+        tasks: &[Vec<(D::Lit, D::Lit)>],
+        config: &SynthConfig<D>,
+        training: Training,
+    ) -> (usize, Vec<usize>) {
+        let candidates = tasks
+            .iter()
+            .filter(|examples| examples.len() > 1)
+            .filter_map(|examples| self.candidates::<D>(examples, config))
+            .collect::<Vec<_>>();
+
+        let solved = (0..training.epochs)
+            .map(|_| {
+                let mut solved = 0;
+                for (right, wrong) in &candidates {
+                    let best = right
+                        .iter()
+                        .min_by(|l, r| self.score_features(l).total_cmp(&self.score_features(r)))
+                        .unwrap();
+                    let best_score = self.score_features(best);
+                    let mut first = true;
+                    for features in wrong {
+                        if self.score_features(features) < best_score + training.margin {
+                            first &= self.score_features(features) >= best_score;
+                            self.update(best, features, training.learning_rate);
+                        }
+                    }
+                    if first {
+                        solved += 1;
+                    }
+                }
+                solved
+            })
+            .collect();
+        (candidates.len(), solved)
+    }
+
+    // the features of the right and wrong candidates for a task, if it has both
+    #[allow(clippy::type_complexity)]
+    fn candidates<D: Dsl>(
+        &self,
+        examples: &[(D::Lit, D::Lit)],
+        config: &SynthConfig<D>,
+    ) -> Option<(Vec<Features>, Vec<Features>)> {
+        // the programs that fit the first example, at the size the task is
+        // solved at, the last time they're observed. Their features are taken
+        // on every example, the same as when `pick_best` scores them.
+        let mut first = None;
+        top_down_observed(examples, config, &config.budget(), |_, seen, vsa| {
+            if seen == 1 {
+                first = Some(Arc::new(vsa.clone()));
+            }
+        });
+        let (right, wrong): (Vec<_>, Vec<_>) = VSA::ranked(&first?)
+            .take(self.candidates.max(1))
+            .partition(|ast| examples.iter().all(|(inp, out)| ast.eval(inp) == *out));
+
+        let features = |asts: Vec<ASTOf<D>>| {
+            asts.iter()
+                .map(|ast| D::features(ast, examples))
+                .collect::<Vec<_>>()
+        };
+        if right.is_empty() || wrong.is_empty() {
+            None
+        } else {
+            Some((features(right), features(wrong)))
+        }
+    }
+
+    // move the weights so that `right` scores lower than `wrong`
+    fn update(&mut self, right: &Features, wrong: &Features, rate: f64) {
+        for (name, value) in wrong {
+            *self.weights.entry(name.to_string()).or_default() += rate * value;
+        }
+        for (name, value) in right {
+            *self.weights.entry(name.to_string()).or_default() -= rate * value;
+        }
+    }
+}
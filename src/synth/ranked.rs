@@ -0,0 +1,234 @@
+use std::{
+    cell::RefCell,
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap, HashSet},
+    rc::Rc,
+    sync::Arc,
+};
+
+use super::vsa::{Cost, InputLit, Language, AST, VSA};
+
+// Lazily enumerates the programs in a VSA, cheapest first.
+//
+// The cost of an App is the cost of its function plus the sizes of its
+// arguments, so below the root everything is enumerated smallest first. Unions
+// merge their children's streams, and Joins walk the product of their
+// children's streams best first using a heap of index tuples. Every node's
+// stream is shared, so a DAG is only expanded once per node.
+pub struct Ranked<L, F>
+where
+    L: Clone + Eq + std::hash::Hash + std::fmt::Debug + InputLit + Cost,
+    F: Language<L> + Eq + Copy + std::hash::Hash + std::fmt::Debug + Cost,
+{
+    root: Shared<L, F>,
+    next: usize,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+enum Order {
+    Cost,
+    Size,
+}
+
+type Shared<L, F> = Rc<RefCell<Stream<L, F>>>;
+type Memo<L, F> = HashMap<(*const VSA<L, F>, Order), Shared<L, F>>;
+
+struct Stream<L, F>
+where
+    L: Clone + Eq + std::hash::Hash + std::fmt::Debug + InputLit + Cost,
+    F: Language<L> + Eq + Copy + std::hash::Hash + std::fmt::Debug + Cost,
+{
+    // everything produced so far, in order
+    items: Vec<(usize, AST<L, F>)>,
+    kind: Kind<L, F>,
+}
+
+enum Kind<L, F>
+where
+    L: Clone + Eq + std::hash::Hash + std::fmt::Debug + InputLit + Cost,
+    F: Language<L> + Eq + Copy + std::hash::Hash + std::fmt::Debug + Cost,
+{
+    Leaf(std::vec::IntoIter<(usize, AST<L, F>)>),
+    Union {
+        children: Vec<(Shared<L, F>, usize)>,
+        seen: HashSet<AST<L, F>>,
+    },
+    Join {
+        op: F,
+        base: usize,
+        children: Vec<Shared<L, F>>,
+        heap: BinaryHeap<Reverse<(usize, Vec<usize>)>>,
+        visited: HashSet<Vec<usize>>,
+    },
+}
+
+impl<L, F> Ranked<L, F>
+where
+    L: Clone + Eq + std::hash::Hash + std::fmt::Debug + InputLit + Cost,
+    F: Language<L> + Eq + Copy + std::hash::Hash + std::fmt::Debug + Cost,
+{
+    pub fn new(vsa: &Arc<VSA<L, F>>) -> Self {
+        Ranked {
+            root: Stream::shared(vsa, Order::Cost, &mut HashMap::new()),
+            next: 0,
+        }
+    }
+}
+
+impl<L, F> Iterator for Ranked<L, F>
+where
+    L: Clone + Eq + std::hash::Hash + std::fmt::Debug + InputLit + Cost,
+    F: Language<L> + Eq + Copy + std::hash::Hash + std::fmt::Debug + Cost,
+{
+    type Item = AST<L, F>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (_, ast) = Stream::get(&self.root, self.next)?;
+        self.next += 1;
+        Some(ast)
+    }
+}
+
+impl<L, F> Stream<L, F>
+where
+    L: Clone + Eq + std::hash::Hash + std::fmt::Debug + InputLit + Cost,
+    F: Language<L> + Eq + Copy + std::hash::Hash + std::fmt::Debug + Cost,
+{
+    fn shared(vsa: &Arc<VSA<L, F>>, order: Order, memo: &mut Memo<L, F>) -> Shared<L, F> {
+        let key = (Arc::as_ptr(vsa), order);
+        if let Some(stream) = memo.get(&key) {
+            return stream.clone();
+        }
+
+        let kind = match vsa.as_ref() {
+            VSA::Leaf(asts) => {
+                let mut items = asts
+                    .iter()
+                    .map(|ast| {
+                        let key = match order {
+                            Order::Cost => ast.cost(),
+                            Order::Size => ast.size(),
+                        };
+                        (key, ast.as_ref().clone())
+                    })
+                    .collect::<Vec<_>>();
+                items.sort_by_key(|(key, _)| *key);
+                Kind::Leaf(items.into_iter())
+            }
+            VSA::Union(vsas) => Kind::Union {
+                children: vsas
+                    .iter()
+                    .map(|vsa| (Stream::shared(vsa, order, memo), 0))
+                    .collect(),
+                seen: HashSet::new(),
+            },
+            VSA::Join { op, children, .. } => {
+                let children = children
+                    .iter()
+                    .map(|vsa| Stream::shared(vsa, Order::Size, memo))
+                    .collect::<Vec<_>>();
+                let base = match order {
+                    Order::Cost => op.cost(),
+                    Order::Size => 1,
+                };
+
+                let mut heap = BinaryHeap::new();
+                let mut visited = HashSet::new();
+                let start = vec![0; children.len()];
+                if let Some(key) = Stream::join_key(&children, base, &start) {
+                    visited.insert(start.clone());
+                    heap.push(Reverse((key, start)));
+                }
+
+                Kind::Join {
+                    op: *op,
+                    base,
+                    children,
+                    heap,
+                    visited,
+                }
+            }
+            VSA::Unlearned { .. } => Kind::Leaf(Vec::new().into_iter()),
+        };
+
+        let stream = Rc::new(RefCell::new(Stream {
+            items: Vec::new(),
+            kind,
+        }));
+        memo.insert(key, stream.clone());
+        stream
+    }
+
+    // the i-th best program of the stream along with its key
+    fn get(stream: &Shared<L, F>, i: usize) -> Option<(usize, AST<L, F>)> {
+        loop {
+            if let Some(item) = stream.borrow().items.get(i) {
+                return Some(item.clone());
+            }
+
+            let item = stream.borrow_mut().produce()?;
+            stream.borrow_mut().items.push(item);
+        }
+    }
+
+    fn key_at(stream: &Shared<L, F>, i: usize) -> Option<usize> {
+        Stream::get(stream, i).map(|(key, _)| key)
+    }
+
+    fn join_key(children: &[Shared<L, F>], base: usize, indices: &[usize]) -> Option<usize> {
+        children
+            .iter()
+            .zip(indices)
+            .map(|(child, i)| Stream::key_at(child, *i))
+            .sum::<Option<usize>>()
+            .map(|sum| base + sum)
+    }
+
+    fn produce(&mut self) -> Option<(usize, AST<L, F>)> {
+        match &mut self.kind {
+            Kind::Leaf(items) => items.next(),
+            Kind::Union { children, seen } => loop {
+                let (best, _) = children
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(j, (child, i))| Some((j, Stream::key_at(child, *i)?)))
+                    .min_by_key(|(_, key)| *key)?;
+
+                let (child, i) = &mut children[best];
+                let item = Stream::get(child, *i).unwrap();
+                *i += 1;
+                if seen.insert(item.1.clone()) {
+                    return Some(item);
+                }
+            },
+            Kind::Join {
+                op,
+                base,
+                children,
+                heap,
+                visited,
+            } => {
+                let Reverse((key, indices)) = heap.pop()?;
+
+                for j in 0..indices.len() {
+                    let mut next = indices.clone();
+                    next[j] += 1;
+                    if visited.contains(&next) {
+                        continue;
+                    }
+                    if let Some(next_key) = Stream::join_key(children, *base, &next) {
+                        visited.insert(next.clone());
+                        heap.push(Reverse((next_key, next)));
+                    }
+                }
+
+                let args = children
+                    .iter()
+                    .zip(&indices)
+                    .map(|(child, i)| Stream::get(child, *i).unwrap().1)
+                    .collect();
+                Some((key, AST::App { fun: *op, args }))
+            }
+        }
+    }
+}
@@ -0,0 +1,165 @@
+use std::{collections::HashMap, sync::Arc};
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use super::vsa::{Cost, InputLit, Language, AST, VSA};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Weighting {
+    // every program in the VSA is equally likely
+    Uniform,
+    // each unit of cost multiplies a program's weight by `decay`, which should
+    // be in (0, 1], so cheap programs come up more often
+    Cost { decay: f64 },
+}
+
+// Draws random programs from a VSA, forever.
+//
+// A program's weight is `decay` to the power of its cost, or 1 for uniform
+// sampling. The total weight below each node is computed once, then a sample
+// picks a child of each Union in proportion to its weight and samples every
+// child of a Join on its own. Like the cost, the weight of an App only depends
+// on the sizes of its arguments, so below the root nodes are weighted by size.
+// Programs reachable through several children of a Union are counted once per
+// path. The same seed gives the same programs from the same VSA.
+// a node and whether it's weighted by size
+type Key<L, F> = (*const VSA<L, F>, bool);
+type Weighted<L, F> = Vec<(f64, AST<L, F>)>;
+
+pub struct Sampler<L, F>
+where
+    L: Clone + Eq + std::hash::Hash + std::fmt::Debug + InputLit + Cost,
+    F: Language<L> + Eq + Copy + std::hash::Hash + std::fmt::Debug + Cost,
+{
+    root: Arc<VSA<L, F>>,
+    weighting: Weighting,
+    rng: StdRng,
+    weights: HashMap<Key<L, F>, f64>,
+    // leaf programs with their weights, in a fixed order
+    leaves: HashMap<Key<L, F>, Weighted<L, F>>,
+}
+
+impl<L, F> Sampler<L, F>
+where
+    L: Clone + Eq + std::hash::Hash + std::fmt::Debug + InputLit + Cost,
+    F: Language<L> + Eq + Copy + std::hash::Hash + std::fmt::Debug + Cost,
+{
+    pub fn new(vsa: &Arc<VSA<L, F>>, weighting: Weighting, seed: u64) -> Self {
+        Sampler {
+            root: vsa.clone(),
+            weighting,
+            rng: StdRng::seed_from_u64(seed),
+            weights: HashMap::new(),
+            leaves: HashMap::new(),
+        }
+    }
+
+    fn weight_of(&self, measure: usize) -> f64 {
+        match self.weighting {
+            Weighting::Uniform => 1.0,
+            Weighting::Cost { decay } => decay.powi(measure as i32),
+        }
+    }
+
+    fn weight(&mut self, vsa: &VSA<L, F>, by_size: bool) -> f64 {
+        let key = (vsa as *const VSA<L, F>, by_size);
+        if let Some(weight) = self.weights.get(&key) {
+            return *weight;
+        }
+
+        let weight = match vsa {
+            VSA::Leaf(asts) => {
+                let mut entries = asts
+                    .iter()
+                    .map(|ast| {
+                        let measure = if by_size { ast.size() } else { ast.cost() };
+                        (self.weight_of(measure), ast.as_ref().clone())
+                    })
+                    .collect::<Vec<_>>();
+                // the set's order changes from run to run
+                entries.sort_by_cached_key(|(_, ast)| format!("{:?}", ast));
+                let weight = entries.iter().map(|(weight, _)| weight).sum();
+                self.leaves.insert(key, entries);
+                weight
+            }
+            VSA::Union(vsas) => vsas.iter().map(|vsa| self.weight(vsa, by_size)).sum(),
+            VSA::Join { op, children, .. } => {
+                let base = self.weight_of(if by_size { 1 } else { op.cost() });
+                children
+                    .iter()
+                    .map(|vsa| self.weight(vsa, true))
+                    .product::<f64>()
+                    * base
+            }
+            VSA::Unlearned { .. } => 0.0,
+        };
+
+        self.weights.insert(key, weight);
+        weight
+    }
+
+    // index of a random item, in proportion to the weights
+    fn choose(&mut self, weights: &[f64]) -> Option<usize> {
+        let total = weights.iter().sum::<f64>();
+        if total <= 0.0 {
+            return None;
+        }
+
+        let mut r = self.rng.gen::<f64>() * total;
+        let mut last = None;
+        for (i, &weight) in weights.iter().enumerate().filter(|(_, w)| **w > 0.0) {
+            if r < weight {
+                return Some(i);
+            }
+            r -= weight;
+            last = Some(i);
+        }
+        // rounding error
+        last
+    }
+
+    fn draw(&mut self, vsa: &VSA<L, F>, by_size: bool) -> Option<AST<L, F>> {
+        match vsa {
+            VSA::Leaf(_) => {
+                self.weight(vsa, by_size);
+                let key = (vsa as *const VSA<L, F>, by_size);
+                let weights = self.leaves[&key]
+                    .iter()
+                    .map(|(weight, _)| *weight)
+                    .collect::<Vec<_>>();
+                let i = self.choose(&weights)?;
+                Some(self.leaves[&key][i].1.clone())
+            }
+            VSA::Union(vsas) => {
+                let weights = vsas
+                    .iter()
+                    .map(|vsa| self.weight(vsa, by_size))
+                    .collect::<Vec<_>>();
+                let i = self.choose(&weights)?;
+                self.draw(&vsas[i], by_size)
+            }
+            VSA::Join { op, children, .. } => {
+                let args = children
+                    .iter()
+                    .map(|vsa| self.draw(vsa, true))
+                    .collect::<Option<_>>()?;
+                Some(AST::App { fun: *op, args })
+            }
+            VSA::Unlearned { .. } => None,
+        }
+    }
+}
+
+impl<L, F> Iterator for Sampler<L, F>
+where
+    L: Clone + Eq + std::hash::Hash + std::fmt::Debug + InputLit + Cost,
+    F: Language<L> + Eq + Copy + std::hash::Hash + std::fmt::Debug + Cost,
+{
+    type Item = AST<L, F>;
+
+    // None only if the VSA has no programs
+    fn next(&mut self) -> Option<Self::Item> {
+        let root = self.root.clone();
+        self.draw(&root, false)
+    }
+}
@@ -0,0 +1,126 @@
+use itertools::Itertools;
+
+use super::regex;
+
+// What `Find` and `FindEnd` look for in a string. Tokens are turned into a
+// regex by `Token::pattern`, literals are escaped so they only match
+// themselves.
+#[derive(PartialEq, Eq, Hash, Clone, Debug)]
+pub enum Token {
+    // a string matched as is
+    Literal(String),
+    Class(Class),
+    // a regex that isn't one of the above, e.g. from a config file
+    Regex(Pattern),
+    // tokens matched one after another, made by `Token::then`
+    Seq(Vec<Token>),
+}
+
+// a regex that compiles, only made by `Token::regex`
+#[derive(PartialEq, Eq, Hash, Clone, Debug)]
+pub struct Pattern(String);
+
+impl Pattern {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+// FlashFill style character classes
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub enum Class {
+    Digits,
+    Alpha,
+    Upper,
+    Lower,
+    Whitespace,
+    Punctuation,
+    Start,
+    End,
+}
+
+impl Class {
+    pub const ALL: [Class; 8] = [
+        Class::Digits,
+        Class::Alpha,
+        Class::Upper,
+        Class::Lower,
+        Class::Whitespace,
+        Class::Punctuation,
+        Class::Start,
+        Class::End,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Class::Digits => "digits",
+            Class::Alpha => "alpha",
+            Class::Upper => "upper",
+            Class::Lower => "lower",
+            Class::Whitespace => "whitespace",
+            Class::Punctuation => "punctuation",
+            Class::Start => "start",
+            Class::End => "end",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Class> {
+        Class::ALL.into_iter().find(|class| class.name() == name)
+    }
+
+    // runs of digits, letters and whitespace, single upper and lower case
+    // letters and punctuation
+    pub fn pattern(self) -> &'static str {
+        match self {
+            Class::Digits => r"\d+",
+            Class::Alpha => r"\p{Alphabetic}+",
+            Class::Upper => r"\p{Uppercase}",
+            Class::Lower => r"\p{Lowercase}",
+            Class::Whitespace => r"\s+",
+            Class::Punctuation => r"\p{Punctuation}",
+            Class::Start => "^",
+            Class::End => "$",
+        }
+    }
+}
+
+impl Token {
+    // a regex token, if it compiles
+    pub fn regex(pattern: &str) -> Result<Token, regex::Error> {
+        regex(pattern)?;
+        Ok(Token::Regex(Pattern(pattern.to_string())))
+    }
+
+    pub fn pattern(&self) -> String {
+        match self {
+            Token::Literal(s) => regex::escape(s),
+            Token::Class(class) => class.pattern().to_string(),
+            // a group so that alternatives stay inside of it in a Seq
+            Token::Regex(re) => format!("(?:{})", re.as_str()),
+            Token::Seq(tokens) => tokens.iter().map(Token::pattern).collect(),
+        }
+    }
+
+    // the tokens of `self` followed by the tokens of `other`, if they still
+    // compile together
+    pub fn then(&self, other: &Token) -> Result<Token, regex::Error> {
+        let parts = |token: &Token| match token {
+            Token::Seq(tokens) => tokens.clone(),
+            _ => vec![token.clone()],
+        };
+        let seq = Token::Seq([parts(self), parts(other)].concat());
+        regex(&seq.pattern())?;
+        Ok(seq)
+    }
+}
+
+impl std::fmt::Display for Token {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Token::Literal(s) => write!(f, "'{s}'"),
+            Token::Class(class) => write!(f, "{}", class.name()),
+            Token::Regex(re) => write!(f, "/{}/", re.as_str()),
+            Token::Seq(tokens) => write!(f, "({})", tokens.iter().join(" <> ")),
+        }
+    }
+}
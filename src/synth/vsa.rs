@@ -1,8 +1,54 @@
 use itertools::Itertools;
-use std::{collections::HashMap, collections::HashSet, fmt::Display, rc::Rc};
+use serde::Deserialize;
+use std::{collections::HashMap, collections::HashSet, fmt::Display, sync::Arc};
+
+use super::budget::Budget;
+use super::ranked::Ranked;
+use super::sample::{Sampler, Weighting};
+use super::token::Token;
+
+// the most programs of a learned node that get checked one by one against the
+// examples of an unlearned node it's intersected with
+const MAX_CHECKED_PROGRAMS: usize = 1000;
 
 pub trait Language<L> {
     fn eval(&self, args: &[L], input: &L) -> L;
+
+    // For higher order functions, whether the argument at `index` is a lambda
+    // over X instead of a value
+    fn is_lambda(&self, _index: usize) -> bool {
+        false
+    }
+
+    // The inputs a lambda argument is applied to, given the arguments before
+    // it. `eval` gets its output on each of them in its place.
+    fn lambda_inputs(&self, _args: &[L], _input: &L) -> Vec<L> {
+        Vec::new()
+    }
+
+    fn has_lambda(&self, arity: usize) -> bool {
+        (0..arity).any(|i| self.is_lambda(i))
+    }
+}
+
+// Evaluates the arguments of an App in order, with lambdas applied to each of
+// their inputs rather than to the outer one.
+fn eval_args<L, F: Language<L>, A>(
+    fun: &F,
+    args: &[A],
+    input: &L,
+    eval: impl Fn(&A, &L) -> L,
+) -> Vec<L> {
+    let mut evaled = Vec::with_capacity(args.len());
+    for (i, arg) in args.iter().enumerate() {
+        if fun.is_lambda(i) {
+            let inputs = fun.lambda_inputs(&evaled, input);
+            evaled.extend(inputs.iter().map(|inp| eval(arg, inp)));
+        } else {
+            evaled.push(eval(arg, input));
+        }
+    }
+    evaled
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -11,19 +57,49 @@ where
     L: Clone + Eq + std::hash::Hash + std::fmt::Debug + InputLit,
     F: Language<L> + std::hash::Hash + std::fmt::Debug + Eq,
 {
-    Leaf(HashSet<Rc<AST<L, F>>>),
-    Union(Vec<Rc<VSA<L, F>>>),
+    Leaf(HashSet<Arc<AST<L, F>>>),
+    Union(Vec<Arc<VSA<L, F>>>),
     Join {
         op: F,
-        children: Vec<Rc<VSA<L, F>>>,
+        children: Vec<Arc<VSA<L, F>>>,
         children_goals: Vec<L>,
     },
     Unlearned {
         start: L,
         goal: L,
+        // more examples a program learned here has to agree with, picked up
+        // from intersecting with other examples' VSAs
+        others: Vec<(L, L)>,
     },
 }
 
+// How big and how ambiguous a VSA is. Shared nodes are counted once for
+// `nodes` and `edges`, but programs are counted once per path to them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct VSAStats {
+    // saturates at u128::MAX
+    pub programs: u128,
+    pub nodes: usize,
+    pub edges: usize,
+    pub depth: usize,
+}
+
+impl Display for VSAStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.programs == u128::MAX {
+            write!(f, "at least ")?;
+        }
+        write!(
+            f,
+            "{} programs ({} nodes, {} edges, depth {})",
+            self.programs, self.nodes, self.edges, self.depth
+        )
+    }
+}
+
+// the parts of a VSA with the same output on some input, see `VSA::cluster`
+pub type Clusters<L, F> = HashMap<Option<L>, Arc<VSA<L, F>>>;
+
 impl<L, F> Default for VSA<L, F>
 where
     L: std::hash::Hash + Eq + Clone + std::fmt::Debug + InputLit,
@@ -43,7 +119,7 @@ where
         VSA::Leaf(HashSet::new())
     }
 
-    pub fn unify(left: Rc<VSA<L, F>>, right: Rc<VSA<L, F>>) -> Self {
+    pub fn unify(left: Arc<VSA<L, F>>, right: Arc<VSA<L, F>>) -> Self {
         match (left.as_ref(), right.as_ref()) {
             (VSA::Leaf(l), VSA::Leaf(r)) => VSA::Leaf(l.union(r).cloned().collect()),
             (VSA::Union(u), _) => VSA::Union(
@@ -63,7 +139,7 @@ where
     }
 
     pub fn singleton(ast: AST<L, F>) -> Self {
-        VSA::Leaf(std::iter::once(Rc::new(ast)).collect())
+        VSA::Leaf(std::iter::once(Arc::new(ast)).collect())
     }
 
     pub fn eval(&self, inp: &L) -> L {
@@ -72,10 +148,7 @@ where
             VSA::Leaf(c) => c.iter().next().unwrap().clone().eval(inp),
             VSA::Union(c) => c[0].eval(inp),
             VSA::Join { op, children, .. } => {
-                let cs = children
-                    .iter()
-                    .map(|vsa| vsa.clone().eval(inp))
-                    .collect::<Vec<_>>();
+                let cs = eval_args(op, children, inp, |vsa, inp| vsa.eval(inp));
                 op.eval(&cs, inp)
             }
             VSA::Unlearned { goal, .. } => goal.clone(),
@@ -83,29 +156,97 @@ where
     }
 
     fn contains(&self, program: &AST<L, F>) -> bool {
-        match self {
+        self.contains_memo(program, &mut HashMap::new())
+    }
+
+    // keyed by the addresses of the node and the part of the program, so
+    // shared nodes are only searched once for each part
+    #[allow(clippy::type_complexity)]
+    fn contains_memo(
+        &self,
+        program: &AST<L, F>,
+        memo: &mut HashMap<(*const Self, *const AST<L, F>), bool>,
+    ) -> bool {
+        let key = (self as *const Self, program as *const AST<L, F>);
+        if let Some(found) = memo.get(&key) {
+            return *found;
+        }
+
+        let found = match self {
             VSA::Leaf(s) => s.contains(program),
-            VSA::Union(vss) => vss.iter().any(|vs| vs.contains(program)),
+            VSA::Union(vss) => vss.iter().any(|vs| vs.contains_memo(program, memo)),
             VSA::Join { op, children, .. } => match program {
                 AST::App { fun, args } if fun == op => args
                     .iter()
-                    .all(|arg| children.iter().any(|vss| vss.contains(arg))),
+                    .all(|arg| children.iter().any(|vss| vss.contains_memo(arg, memo))),
                 _ => false,
             },
             VSA::Unlearned { .. } => false,
-        }
+        };
+
+        memo.insert(key, found);
+        found
     }
 
     // https://dl.acm.org/doi/pdf/10.1145/2858965.2814310
     // page 10
     pub fn intersect(&self, other: &VSA<L, F>) -> VSA<L, F> {
-        match (self, other) {
-            (vsa, VSA::Union(union)) | (VSA::Union(union), vsa) => VSA::Union(
-                union
+        self.intersect_within(other, &Budget::unlimited())
+    }
+
+    // once the budget runs out, whatever is left to intersect is
+    // over approximated by `self`, see `intersect_checked`
+    pub fn intersect_within(&self, other: &VSA<L, F>, budget: &Budget) -> VSA<L, F> {
+        self.intersect_checked(other, budget).0
+    }
+
+    // Like `intersect_within`, but also says whether the result is over
+    // approximated, so that callers know to check the programs they pick
+    // against the examples. That happens when the budget runs out, and when an
+    // unlearned node meets a learned Join that's too big to check program by
+    // program or still has unlearned nodes of its own.
+    pub fn intersect_checked(&self, other: &VSA<L, F>, budget: &Budget) -> (VSA<L, F>, bool) {
+        let mut approximate = false;
+        let vsa = self.intersect_rec(other, budget, &mut HashMap::new(), &mut approximate);
+        (Arc::unwrap_or_clone(vsa), approximate)
+    }
+
+    // Every pair of nodes is intersected once, keyed by their addresses, so a
+    // DAG stays one instead of being unfolded into a tree. Each new node is
+    // paid for before its children are built.
+    #[allow(clippy::type_complexity)]
+    fn intersect_rec(
+        &self,
+        other: &VSA<L, F>,
+        budget: &Budget,
+        memo: &mut HashMap<(*const Self, *const Self), Arc<VSA<L, F>>>,
+        approximate: &mut bool,
+    ) -> Arc<VSA<L, F>> {
+        let key = (self as *const Self, other as *const Self);
+        if let Some(vsa) = memo.get(&key) {
+            return vsa.clone();
+        }
+
+        if budget.spend_vsa_nodes(1).is_err() {
+            *approximate = true;
+            return Arc::new(self.clone());
+        }
+
+        let vsa = match (self, other) {
+            // empty intersections are left out, most pairs of nodes have
+            // nothing in common and there's no need to keep them around
+            (vsa, VSA::Union(union)) | (VSA::Union(union), vsa) => {
+                let union = union
                     .iter()
-                    .map(|n1| Rc::new(n1.clone().intersect(vsa)))
-                    .collect(),
-            ),
+                    .map(|n1| n1.intersect_rec(vsa, budget, memo, approximate))
+                    .filter(|vsa| !vsa.is_empty_node())
+                    .collect::<Vec<_>>();
+                if union.is_empty() {
+                    VSA::empty()
+                } else {
+                    VSA::Union(union)
+                }
+            }
 
             #[rustfmt::skip]
             (VSA::Join { op: l_op, .. }, VSA::Join { op: r_op, .. })
@@ -113,11 +254,22 @@ where
 
             #[rustfmt::skip]
             (VSA::Join { op, children: l_children, children_goals }, VSA::Join { op: _, children: r_children, .. })
-                => VSA::Join {
-                    op: *op,
-                    children: l_children.iter().zip(r_children).map(|(l, r)| Rc::new(l.intersect(r))).collect(),
-                    children_goals: children_goals.clone()
-                },
+                => {
+                    // one empty child is enough for the Join to be empty
+                    let mut children = Vec::with_capacity(l_children.len());
+                    for (l, r) in l_children.iter().zip(r_children) {
+                        let child = l.intersect_rec(r, budget, memo, approximate);
+                        if child.is_empty_node() {
+                            break;
+                        }
+                        children.push(child);
+                    }
+                    if children.len() < l_children.len() {
+                        VSA::empty()
+                    } else {
+                        VSA::Join { op: *op, children, children_goals: children_goals.clone() }
+                    }
+                }
 
             #[rustfmt::skip]
             (VSA::Join { op, children, .. }, VSA::Leaf(s)) | (VSA::Leaf(s), VSA::Join { op, children, .. })
@@ -133,66 +285,217 @@ where
                 VSA::Leaf(l_set.intersection(r_set).cloned().collect())
             }
 
-            (VSA::Unlearned { .. }, _) | (_, VSA::Unlearned { .. }) => {
-                // TODO: potentially build an intersection with unlearned
-                // so we know that it's worth learning
-                //
-                // We can intersect with unlearned
-                // 1. when we know the start/goal of the other node
-                // 2. if both are unlearned w/ the same input
-                // 3. if the other node is a leaf we can filter the asts for those that fit the
-                //    unlearned
-                // VSA::empty()
-                self.clone()
+            // both still have to be learned, so from now on for every example
+            (
+                VSA::Unlearned {
+                    start,
+                    goal,
+                    others,
+                },
+                hole @ VSA::Unlearned { .. },
+            ) => {
+                let mut others = others.clone();
+                for example in hole.hole_examples() {
+                    if example != (start.clone(), goal.clone()) && !others.contains(&example) {
+                        others.push(example);
+                    }
+                }
+                VSA::Unlearned {
+                    start: start.clone(),
+                    goal: goal.clone(),
+                    others,
+                }
+            }
+
+            (hole @ VSA::Unlearned { .. }, vsa) | (vsa, hole @ VSA::Unlearned { .. }) => {
+                let examples = hole.hole_examples();
+                let fits =
+                    |ast: &AST<L, F>| examples.iter().all(|(inp, out)| ast.eval(inp) == *out);
+                match vsa {
+                    VSA::Leaf(s) => VSA::Leaf(s.iter().filter(|ast| fits(ast)).cloned().collect()),
+                    // a learned Join can be checked program by program as long
+                    // as it's small, otherwise it's left over approximated
+                    _ => match vsa.programs(MAX_CHECKED_PROGRAMS) {
+                        Some(programs) => VSA::Leaf(
+                            programs
+                                .into_iter()
+                                .filter(|ast| fits(ast))
+                                .map(Arc::new)
+                                .collect(),
+                        ),
+                        None => {
+                            *approximate = true;
+                            vsa.clone()
+                        }
+                    },
+                }
             }
+        };
+
+        let vsa = Arc::new(vsa);
+        memo.insert(key, vsa.clone());
+        vsa
+    }
+
+    // a node without any programs in it, without looking at its children
+    fn is_empty_node(&self) -> bool {
+        match self {
+            VSA::Leaf(s) => s.is_empty(),
+            VSA::Union(vsas) => vsas.is_empty(),
+            _ => false,
         }
     }
 
-    fn group_by(map: HashMap<L, Rc<VSA<L, F>>>) -> HashMap<L, Rc<VSA<L, F>>> {
-        // TODO: do it in O(n)
-        map.iter()
-            .map(|(o1, _)| {
-                (
-                    o1.clone(),
-                    Rc::new(VSA::Union(
-                        map.iter()
-                            .filter(|(o2, _)| &o1 == o2)
-                            .map(|(_, v)| v.clone())
-                            .collect(),
-                    )),
+    // the examples an unlearned node has to be learned for
+    pub fn hole_examples(&self) -> Vec<(L, L)> {
+        match self {
+            VSA::Unlearned {
+                start,
+                goal,
+                others,
+            } => std::iter::once((start.clone(), goal.clone()))
+                .chain(others.iter().cloned())
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    // every program in the VSA, unless there are more than `limit` or some
+    // part of it is still unlearned
+    fn programs(&self, limit: usize) -> Option<Vec<AST<L, F>>> {
+        match self {
+            VSA::Leaf(s) if s.len() <= limit => {
+                Some(s.iter().map(|ast| ast.as_ref().clone()).collect())
+            }
+            VSA::Leaf(_) => None,
+            VSA::Union(vsas) => {
+                let mut programs = Vec::new();
+                for vsa in vsas {
+                    programs.extend(vsa.programs(limit)?);
+                    if programs.len() > limit {
+                        return None;
+                    }
+                }
+                Some(programs)
+            }
+            VSA::Join { op, children, .. } => {
+                let args = children
+                    .iter()
+                    .map(|vsa| vsa.programs(limit))
+                    .collect::<Option<Vec<_>>>()?;
+                let total = args
+                    .iter()
+                    .try_fold(1usize, |acc, args| acc.checked_mul(args.len()))?;
+                if total > limit {
+                    return None;
+                }
+                Some(
+                    args.into_iter()
+                        .multi_cartesian_product()
+                        .map(|args| AST::App { fun: *op, args })
+                        .collect(),
                 )
+            }
+            VSA::Unlearned { .. } => None,
+        }
+    }
+
+    // one VSA per key, a Union if there's more than one
+    fn group_by(vsas: impl IntoIterator<Item = (Option<L>, Arc<VSA<L, F>>)>) -> Clusters<L, F> {
+        let mut groups: HashMap<Option<L>, Vec<Arc<VSA<L, F>>>> = HashMap::new();
+        for (out, vsa) in vsas {
+            groups.entry(out).or_default().push(vsa);
+        }
+
+        groups
+            .into_iter()
+            .map(|(out, mut vsas)| {
+                let vsa = if vsas.len() == 1 {
+                    vsas.pop().unwrap()
+                } else {
+                    Arc::new(VSA::Union(vsas))
+                };
+                (out, vsa)
             })
             .collect()
     }
 
-    pub fn pick_best(&self, rank: impl Fn(&AST<L, F>) -> usize + Copy) -> Option<AST<L, F>> {
-        match self {
+    // the cheapest program in the VSA along with its cost
+    pub fn pick_best(&self) -> Option<(usize, AST<L, F>)>
+    where
+        L: Cost,
+        F: Cost,
+    {
+        self.best(false, &mut HashMap::new())
+    }
+
+    // The cost of an App only depends on the cost of its function and the
+    // sizes of its arguments, so the cheapest program of a Join is built from
+    // the smallest program of each child. Every node is solved once per
+    // measure, keyed by its address, so shared parts of the DAG aren't redone.
+    #[allow(clippy::type_complexity)]
+    fn best(
+        &self,
+        by_size: bool,
+        memo: &mut HashMap<(*const Self, bool), Option<(usize, AST<L, F>)>>,
+    ) -> Option<(usize, AST<L, F>)>
+    where
+        L: Cost,
+        F: Cost,
+    {
+        let key = (self as *const Self, by_size);
+        if let Some(best) = memo.get(&key) {
+            return best.clone();
+        }
+
+        let best = match self {
             VSA::Leaf(s) => s
                 .iter()
-                .sorted_by_key(|ast| rank(ast.as_ref()))
-                .next()
-                .map(|x| x.as_ref())
-                .cloned(),
+                .map(|ast| {
+                    let measure = if by_size { ast.size() } else { ast.cost() };
+                    (measure, ast)
+                })
+                .min_by_key(|(measure, _)| *measure)
+                .map(|(measure, ast)| (measure, ast.as_ref().clone())),
             VSA::Union(s) => s
                 .iter()
-                .filter_map(|vsa| vsa.pick_best(rank))
-                .min_by_key(rank),
-            VSA::Join { op, children, .. } => {
-                let mut args = children.iter().map(|vsa| vsa.pick_best(rank));
-                if args.any(|picked| picked.is_none()) {
-                    None
-                } else {
-                    Some(AST::App {
-                        fun: *op,
-                        args: children
-                            .iter()
-                            .map(|vsa| vsa.pick_best(rank).unwrap())
-                            .collect(),
-                    })
-                }
-            }
+                .filter_map(|vsa| vsa.best(by_size, memo))
+                .min_by_key(|(measure, _)| *measure),
+            VSA::Join { op, children, .. } => children
+                .iter()
+                .map(|vsa| vsa.best(true, memo))
+                .collect::<Option<Vec<_>>>()
+                .map(|picked| {
+                    let base = if by_size { 1 } else { op.cost() };
+                    let (sizes, args): (Vec<_>, Vec<_>) = picked.into_iter().unzip();
+                    (
+                        base + sizes.into_iter().sum::<usize>(),
+                        AST::App { fun: *op, args },
+                    )
+                }),
             VSA::Unlearned { .. } => None,
-        }
+        };
+
+        memo.insert(key, best.clone());
+        best
+    }
+
+    // every program in the VSA, lazily and cheapest first
+    pub fn ranked(vsa: &Arc<VSA<L, F>>) -> Ranked<L, F>
+    where
+        L: Cost,
+        F: Cost,
+    {
+        Ranked::new(vsa)
+    }
+
+    // random programs from the VSA, the same ones for the same seed
+    pub fn sample(vsa: &Arc<VSA<L, F>>, weighting: Weighting, seed: u64) -> Sampler<L, F>
+    where
+        L: Cost,
+        F: Cost,
+    {
+        Sampler::new(vsa, weighting, seed)
     }
 
     pub fn pick_one(&self) -> Option<AST<L, F>> {
@@ -214,43 +517,109 @@ where
         }
     }
 
-    fn cluster(vsa: Rc<VSA<L, F>>, input: &L) -> HashMap<L, Rc<VSA<L, F>>> {
-        match vsa.as_ref() {
-            VSA::Leaf(s) => VSA::group_by(
-                s.iter()
-                    .map(|p| {
-                        (
-                            p.eval(input),
-                            Rc::new(VSA::Leaf(std::iter::once(p.clone()).collect())),
-                        )
-                    })
-                    .collect(),
-            ),
+    // Splits the VSA up by what its programs output on `input`, the clustering
+    // from the PLDI'15 paper. Programs that still have unlearned parts only
+    // have a known output if one of the hole's examples is for `input`,
+    // otherwise they end up under None.
+    pub fn cluster(&self, input: &L) -> Clusters<L, F> {
+        self.cluster_memo(input, &mut HashMap::new())
+    }
+
+    fn cluster_memo(
+        &self,
+        input: &L,
+        memo: &mut HashMap<*const Self, Clusters<L, F>>,
+    ) -> Clusters<L, F> {
+        if let Some(clusters) = memo.get(&(self as *const Self)) {
+            return clusters.clone();
+        }
+
+        let clusters = match self {
+            VSA::Leaf(s) => {
+                let mut groups: HashMap<L, HashSet<Arc<AST<L, F>>>> = HashMap::new();
+                for ast in s {
+                    groups
+                        .entry(ast.eval(input))
+                        .or_default()
+                        .insert(ast.clone());
+                }
+                groups
+                    .into_iter()
+                    .map(|(out, asts)| (Some(out), Arc::new(VSA::Leaf(asts))))
+                    .collect()
+            }
             VSA::Union(s) => VSA::group_by(
-                // the union of all the clusters
                 s.iter()
-                    .map(|vsa| VSA::cluster(vsa.clone(), input))
-                    .reduce(|a, b| a.into_iter().chain(b.into_iter()).collect())
-                    .unwrap(),
+                    .flat_map(|vsa| vsa.cluster_memo(input, memo))
+                    .collect::<Vec<_>>(),
             ),
-            VSA::Join { op, children, .. } => {
-                let ns = children.iter().map(|vsa| VSA::cluster(vsa.clone(), input));
-                VSA::group_by(
-                    ns.map(|m| {
-                        let ast = AST::App {
-                            fun: *op,
-                            args: m.keys().map(|l| AST::Lit(l.clone())).collect(),
+            // a lambda's outputs depend on the parts of the input, not the
+            // input itself, so those are checked program by program as long
+            // as there aren't too many
+            VSA::Join { op, children, .. } if op.has_lambda(children.len()) => {
+                match self.programs(MAX_CHECKED_PROGRAMS) {
+                    Some(programs) => {
+                        let mut groups: HashMap<L, HashSet<Arc<AST<L, F>>>> = HashMap::new();
+                        for ast in programs {
+                            groups
+                                .entry(ast.eval(input))
+                                .or_default()
+                                .insert(Arc::new(ast));
+                        }
+                        groups
+                            .into_iter()
+                            .map(|(out, asts)| (Some(out), Arc::new(VSA::Leaf(asts))))
+                            .collect()
+                    }
+                    None => std::iter::once((None, Arc::new(self.clone()))).collect(),
+                }
+            }
+            VSA::Join {
+                op,
+                children,
+                children_goals,
+            } => {
+                let children_clusters = children
+                    .iter()
+                    .map(|vsa| {
+                        vsa.cluster_memo(input, memo)
+                            .into_iter()
+                            .collect::<Vec<_>>()
+                    })
+                    .collect::<Vec<_>>();
+
+                // every combination of the children's outputs gives a cluster
+                let joins = children_clusters
+                    .into_iter()
+                    .multi_cartesian_product()
+                    .map(|picked| {
+                        let (outs, children): (Vec<_>, Vec<_>) = picked.into_iter().unzip();
+                        let out = outs
+                            .into_iter()
+                            .collect::<Option<Vec<_>>>()
+                            .map(|outs| op.eval(&outs, input));
+                        let join = VSA::Join {
+                            op: *op,
+                            children,
+                            children_goals: children_goals.clone(),
                         };
-                        let res = ast.eval(input);
-                        (res, vsa.clone())
+                        (out, Arc::new(join))
                     })
-                    .collect(),
-                )
+                    .collect::<Vec<_>>();
+                VSA::group_by(joins)
             }
-            VSA::Unlearned { start, goal } => {
-                todo!()
+            VSA::Unlearned { .. } => {
+                let out = self
+                    .hole_examples()
+                    .into_iter()
+                    .find(|(start, _)| start == input)
+                    .map(|(_, goal)| goal);
+                std::iter::once((out, Arc::new(self.clone()))).collect()
             }
-        }
+        };
+
+        memo.insert(self as *const Self, clusters.clone());
+        clusters
     }
 
     pub fn is_empty(&self) -> bool {
@@ -269,20 +638,79 @@ where
         }
     }
 
-    pub fn flatten(vsa: Rc<VSA<L, F>>) -> Rc<VSA<L, F>> {
-        match vsa.as_ref() {
-            VSA::Leaf(s) => Rc::new(VSA::Leaf(s.clone())),
+    pub fn count_programs(&self) -> u128 {
+        self.stats().programs
+    }
+
+    pub fn stats(&self) -> VSAStats {
+        let mut memo = HashMap::new();
+        let (programs, depth) = self.measure(&mut memo);
+        VSAStats {
+            programs,
+            nodes: memo.len(),
+            edges: memo.values().map(|(_, _, edges)| edges).sum(),
+            depth,
+        }
+    }
+
+    // the number of programs and the depth of every node below this one,
+    // along with how many children it has
+    fn measure(&self, memo: &mut HashMap<*const Self, (u128, usize, usize)>) -> (u128, usize) {
+        if let Some((programs, depth, _)) = memo.get(&(self as *const Self)) {
+            return (*programs, *depth);
+        }
+
+        let (programs, depth, edges) = match self {
+            VSA::Leaf(s) => (s.len() as u128, 1, 0),
+            VSA::Union(vsas) | VSA::Join { children: vsas, .. } => {
+                let measured = vsas.iter().map(|vsa| vsa.measure(memo)).collect::<Vec<_>>();
+                let programs = match self {
+                    VSA::Union(_) => measured
+                        .iter()
+                        .fold(0u128, |acc, (n, _)| acc.saturating_add(*n)),
+                    _ => measured
+                        .iter()
+                        .fold(1u128, |acc, (n, _)| acc.saturating_mul(*n)),
+                };
+                let depth = 1 + measured.iter().map(|(_, d)| *d).max().unwrap_or(0);
+                (programs, depth, vsas.len())
+            }
+            VSA::Unlearned { .. } => (0, 1, 0),
+        };
+
+        memo.insert(self as *const Self, (programs, depth, edges));
+        (programs, depth)
+    }
+
+    pub fn flatten(vsa: Arc<VSA<L, F>>) -> Arc<VSA<L, F>> {
+        VSA::flatten_memo(vsa, &mut HashMap::new())
+    }
+
+    // shared nodes are flattened once, keyed by their addresses
+    fn flatten_memo(
+        vsa: Arc<VSA<L, F>>,
+        memo: &mut HashMap<*const Self, Arc<VSA<L, F>>>,
+    ) -> Arc<VSA<L, F>> {
+        let key = Arc::as_ptr(&vsa);
+        if let Some(flat) = memo.get(&key) {
+            return flat.clone();
+        }
+
+        let flat = match vsa.as_ref() {
+            VSA::Leaf(s) => Arc::new(VSA::Leaf(s.clone())),
             VSA::Union(s) if s.iter().filter(|vsa| !vsa.is_empty()).count() == 1 => {
                 let vsa = s.iter().find(|vsa| !vsa.is_empty()).unwrap();
-                VSA::flatten(vsa.clone())
+                VSA::flatten_memo(vsa.clone(), memo)
             }
             VSA::Union(s) => {
                 let mut flattened: Vec<_> = s
                     .iter()
                     .flat_map(|vsa| match vsa.as_ref() {
                         VSA::Union(s) => {
-                            let mut nv: Vec<_> =
-                                s.iter().map(|vsa| VSA::flatten(vsa.clone())).collect();
+                            let mut nv: Vec<_> = s
+                                .iter()
+                                .map(|vsa| VSA::flatten_memo(vsa.clone(), memo))
+                                .collect();
                             nv.dedup();
                             nv
                         }
@@ -298,7 +726,7 @@ where
                             if nasts.is_empty() {
                                 None
                             } else {
-                                Some(Rc::new(VSA::Leaf(nasts)))
+                                Some(Arc::new(VSA::Leaf(nasts)))
                             }
                         }
                         VSA::Union(s) if s.is_empty() => None,
@@ -306,7 +734,7 @@ where
                     })
                     .collect();
                 flattened.dedup();
-                Rc::new(VSA::Union(flattened))
+                Arc::new(VSA::Union(flattened))
             }
             VSA::Join {
                 op,
@@ -315,16 +743,19 @@ where
             } => {
                 let children = children
                     .iter()
-                    .map(|vsa| VSA::flatten(vsa.clone()))
+                    .map(|vsa| VSA::flatten_memo(vsa.clone(), memo))
                     .collect();
-                Rc::new(VSA::Join {
+                Arc::new(VSA::Join {
                     op: *op,
                     children,
                     children_goals: children_goals.clone(),
                 })
             }
-            VSA::Unlearned { .. } => vsa,
-        }
+            VSA::Unlearned { .. } => vsa.clone(),
+        };
+
+        memo.insert(key, flat.clone());
+        flat
     }
 }
 
@@ -332,7 +763,7 @@ pub trait Cost {
     fn cost(&self) -> usize;
 }
 
-#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug, Deserialize)]
 pub enum Fun {
     Concat,
     Find,
@@ -344,6 +775,14 @@ pub enum Fun {
     Uppercase,
     ConcatMap,
     Equal,
+    ParseInt,
+    FormatInt,
+    Add,
+    Sub,
+    Mul,
+    Div,
+    If,
+    Apply,
 }
 
 impl Cost for Fun {
@@ -357,15 +796,25 @@ impl Cost for Fun {
 
 pub trait InputLit {
     fn is_input(&self) -> bool;
+
+    // what an input literal evaluates to on an example's input
+    fn input_value(&self, inp: &Self) -> Self;
 }
 
 #[derive(PartialEq, Eq, Hash, Clone, Debug)]
 pub enum Lit {
     StringConst(String),
+    // a position in a string, counted in chars
     LocConst(usize),
     BoolConst(bool),
+    IntConst(i64),
     LocEnd,
-    Input,
+    // a column of the input, any column of a plain string is the string
+    Input(usize),
+    // an input with several columns
+    Tuple(Vec<Lit>),
+    // a pattern to find
+    Token(Token),
 }
 
 impl std::fmt::Display for Lit {
@@ -375,22 +824,38 @@ impl std::fmt::Display for Lit {
             StringConst(s) => write!(fmt, "\"{}\"", s),
             LocConst(l) => write!(fmt, "{}", l),
             BoolConst(b) => write!(fmt, "{}", b),
+            IntConst(n) => write!(fmt, "{}", n),
             LocEnd => write!(fmt, "$"),
-            Input => write!(fmt, "X"),
+            Input(0) => write!(fmt, "X"),
+            Input(i) => write!(fmt, "X{}", i),
+            Tuple(cols) => write!(fmt, "({})", cols.iter().join(", ")),
+            Token(token) => write!(fmt, "{}", token),
         }
     }
 }
 
 impl InputLit for Lit {
     fn is_input(&self) -> bool {
-        self == &Lit::Input
+        matches!(self, Lit::Input(_))
+    }
+
+    // columns past the end are empty
+    fn input_value(&self, inp: &Lit) -> Lit {
+        match (self, inp) {
+            (Lit::Input(i), Lit::Tuple(cols)) => cols
+                .get(*i)
+                .cloned()
+                .unwrap_or(Lit::StringConst("".to_string())),
+            (Lit::Input(_), _) => inp.clone(),
+            _ => self.clone(),
+        }
     }
 }
 
 impl Cost for Lit {
     fn cost(&self) -> usize {
         match self {
-            Lit::Input | Lit::LocEnd => 1,
+            Lit::Input(_) | Lit::LocEnd => 1,
             _ => 2,
         }
     }
@@ -426,6 +891,21 @@ impl AST<Lit, Fun> {
             } => input.includes_input(),
         }
     }
+    pub fn map_lits(&self, f: &impl Fn(&Lit) -> Lit) -> Self {
+        match self {
+            AST::Lit(l) => AST::Lit(f(l)),
+            AST::App { fun, args } => AST::App {
+                fun: *fun,
+                args: args.iter().map(|arg| arg.map_lits(f)).collect(),
+            },
+            AST::JS { code, input, typ } => AST::JS {
+                code: code.clone(),
+                input: Box::new(input.map_lits(f)),
+                typ: typ.clone(),
+            },
+        }
+    }
+
     pub fn is_lit(&self) -> bool {
         match self {
             AST::Lit(_) => true,
@@ -438,7 +918,26 @@ impl AST<Lit, Fun> {
 pub enum Typ {
     Str,
     Int,
+    Loc,
     Bool,
+    Tuple,
+    Token,
+}
+
+// the char position of a byte offset on a char boundary, like a regex match's
+pub fn char_pos(s: &str, byte: usize) -> usize {
+    s[..byte].chars().count()
+}
+
+// tokens from `Token::regex` and `Token::then` always compile
+fn token_regex(token: &Token) -> regex::Regex {
+    crate::synth::regex(&token.pattern())
+        .unwrap_or_else(|err| panic!("token {token} doesn't compile: {err}"))
+}
+
+// the byte offset of a char position, None past the end
+pub fn byte_pos(s: &str, pos: usize) -> Option<usize> {
+    s.char_indices().map(|(i, _)| i).chain([s.len()]).nth(pos)
 }
 
 impl Language<Lit> for Fun {
@@ -448,57 +947,77 @@ impl Language<Lit> for Fun {
                 [Lit::StringConst(lhs), Lit::StringConst(rhs)] => {
                     Lit::StringConst(format!("{}{}", lhs, rhs))
                 }
+                // enumeration only concatenates tokens that compile together
+                [Lit::Token(lhs), Lit::Token(rhs)] => Lit::Token(
+                    lhs.then(rhs)
+                        .unwrap_or_else(|err| panic!("{lhs} <> {rhs} doesn't compile: {err}")),
+                ),
+                _ => panic!(),
+            },
+            // the lambda's output on every part of the input
+            Fun::ConcatMap => match args {
+                [Lit::StringConst(_), outs @ ..] => {
+                    let mut buf = String::new();
+                    for out in outs {
+                        match out {
+                            Lit::StringConst(s) => buf.push_str(s),
+                            _ => panic!(),
+                        }
+                    }
+                    Lit::StringConst(buf)
+                }
                 _ => panic!(),
             },
-            Fun::ConcatMap => {
-                // TODO: can't do this yet because of how eval works
-                todo!()
-                // let mut buf = String::new();
-                // Lit::StringConst(buf)
-            }
             Fun::Find => match args {
-                [Lit::StringConst(outer), Lit::StringConst(inner), index] => {
+                [Lit::StringConst(outer), Lit::Token(token), index] => {
                     let i = match index {
                         Lit::LocConst(i) => *i,
-                        Lit::LocEnd => outer.len(),
+                        Lit::LocEnd => outer.chars().count(),
                         _ => panic!(),
                     };
 
-                    use crate::synth::regex;
-                    let re = regex(inner);
-                    let mut found = re.find_iter(outer).map(|m| Lit::LocConst(m.start()));
+                    let re = token_regex(token);
+                    let mut found = re
+                        .find_iter(outer)
+                        .map(|m| Lit::LocConst(char_pos(outer, m.start())));
 
                     found.nth(i).unwrap_or(Lit::LocEnd)
                 }
                 _ => panic!(),
             },
             Fun::FindEnd => match args {
-                [Lit::StringConst(outer), Lit::StringConst(inner), index] => {
+                [Lit::StringConst(outer), Lit::Token(token), index] => {
                     let i = match index {
                         Lit::LocConst(i) => *i,
-                        Lit::LocEnd => outer.len(),
+                        Lit::LocEnd => outer.chars().count(),
                         _ => panic!(),
                     };
 
-                    use crate::synth::regex;
-                    let re = regex(inner);
-                    let mut found = re.find_iter(outer).map(|m| Lit::LocConst(m.end()));
+                    let re = token_regex(token);
+                    let mut found = re
+                        .find_iter(outer)
+                        .map(|m| Lit::LocConst(char_pos(outer, m.end())));
 
                     found.nth(i).unwrap_or(Lit::LocEnd)
                 }
                 _ => panic!(),
             },
-            Fun::Slice => match (args, input) {
-                ([Lit::LocConst(start), Lit::LocConst(end)], Lit::StringConst(s))
-                    if start <= end && end <= &s.len() =>
-                {
-                    Lit::StringConst(s[*start..*end].to_owned())
-                }
-                ([Lit::LocConst(start), Lit::LocEnd], Lit::StringConst(s)) if *start <= s.len() => {
-                    Lit::StringConst(s[*start..].to_owned())
-                }
-                _ => Lit::StringConst("".to_string()),
-            },
+            Fun::Slice => {
+                let slice = match (args, input) {
+                    ([Lit::LocConst(start), Lit::LocConst(end)], Lit::StringConst(s))
+                        if start <= end =>
+                    {
+                        byte_pos(s, *start)
+                            .zip(byte_pos(s, *end))
+                            .map(|(start, end)| &s[start..end])
+                    }
+                    ([Lit::LocConst(start), Lit::LocEnd], Lit::StringConst(s)) => {
+                        byte_pos(s, *start).map(|start| &s[start..])
+                    }
+                    _ => None,
+                };
+                Lit::StringConst(slice.unwrap_or_default().to_string())
+            }
             Fun::LocAdd => match args {
                 [Lit::LocConst(a), Lit::LocConst(b)] => Lit::LocConst(a + b),
                 [Lit::LocEnd, _] | [_, Lit::LocEnd] => Lit::LocEnd,
@@ -513,14 +1032,14 @@ impl Language<Lit> for Fun {
             },
             Fun::Equal => match (args, input) {
                 ([Lit::LocConst(a), Lit::LocConst(b)], _) => Lit::BoolConst(a == b),
-                // ([Lit::LocEnd, Lit::LocEnd], _) => Lit::BoolConst(true),
+                ([Lit::LocEnd, Lit::LocEnd], _) => Lit::BoolConst(true),
                 (
                     [Lit::LocConst(a), Lit::LocEnd] | [Lit::LocEnd, Lit::LocConst(a)],
                     Lit::StringConst(s),
-                ) => Lit::BoolConst(*a == s.len()),
+                ) => Lit::BoolConst(*a == s.chars().count()),
                 ([Lit::StringConst(a), Lit::StringConst(b)], _) => Lit::BoolConst(a == b),
                 (
-                    [Lit::Input, Lit::StringConst(b)] | [Lit::StringConst(b), Lit::Input],
+                    [Lit::Input(_), Lit::StringConst(b)] | [Lit::StringConst(b), Lit::Input(_)],
                     Lit::StringConst(s),
                 ) => Lit::BoolConst(b == s),
                 _ => Lit::BoolConst(false),
@@ -533,6 +1052,63 @@ impl Language<Lit> for Fun {
                 [Lit::StringConst(s)] => Lit::StringConst(s.to_uppercase()),
                 _ => panic!(),
             },
+            // like slicing out of bounds, anything that isn't a number gives 0
+            Fun::ParseInt => match args {
+                [Lit::StringConst(s)] => Lit::IntConst(s.trim().parse().unwrap_or(0)),
+                _ => panic!(),
+            },
+            // padded with zeros to at least `width` chars
+            Fun::FormatInt => match args {
+                [Lit::IntConst(n), Lit::IntConst(width)] => {
+                    let width = (*width).max(0) as usize;
+                    Lit::StringConst(format!("{n:0width$}"))
+                }
+                _ => panic!(),
+            },
+            // overflow and division by zero give 0
+            Fun::Add | Fun::Sub | Fun::Mul | Fun::Div => match args {
+                [Lit::IntConst(a), Lit::IntConst(b)] => {
+                    let res = match self {
+                        Fun::Add => a.checked_add(*b),
+                        Fun::Sub => a.checked_sub(*b),
+                        Fun::Mul => a.checked_mul(*b),
+                        _ => a.checked_div(*b),
+                    };
+                    Lit::IntConst(res.unwrap_or(0))
+                }
+                _ => panic!(),
+            },
+            Fun::If => match args {
+                [Lit::BoolConst(cond), then, els] => {
+                    if *cond {
+                        then.clone()
+                    } else {
+                        els.clone()
+                    }
+                }
+                _ => panic!(),
+            },
+            // the lambda's output on the value
+            Fun::Apply => match args {
+                [_, out] => out.clone(),
+                _ => panic!(),
+            },
+        }
+    }
+
+    fn is_lambda(&self, index: usize) -> bool {
+        matches!(self, Fun::ConcatMap | Fun::Apply) && index == 1
+    }
+
+    // the input split on the separator, an empty one doesn't split
+    fn lambda_inputs(&self, args: &[Lit], input: &Lit) -> Vec<Lit> {
+        match (self, args, input) {
+            (Fun::ConcatMap, [Lit::StringConst(sep)], Lit::StringConst(s)) if !sep.is_empty() => s
+                .split(sep.as_str())
+                .map(|part| Lit::StringConst(part.to_string()))
+                .collect(),
+            (Fun::Apply, [value], _) => vec![value.clone()],
+            _ => vec![input.clone()],
         }
     }
 }
@@ -544,10 +1120,10 @@ where
 {
     pub fn eval(&self, inp: &L) -> L {
         match self {
-            AST::Lit(l) if l.is_input() => inp.clone(),
+            AST::Lit(l) if l.is_input() => l.input_value(inp),
             AST::Lit(l) => l.clone(),
             AST::App { fun, args } => {
-                let evaled = args.iter().map(|ast| ast.eval(inp)).collect::<Vec<_>>();
+                let evaled = eval_args(fun, args, inp, AST::eval);
                 fun.eval(&evaled, inp)
             }
             AST::JS { code, input, typ } => todo!(),
@@ -561,6 +1137,14 @@ where
             AST::JS { code: _, input, .. } => 1 + input.size(),
         }
     }
+
+    pub fn depth(&self) -> usize {
+        match self {
+            AST::Lit(_) => 1,
+            AST::App { args, .. } => 1 + args.iter().map(AST::depth).max().unwrap_or(0),
+            AST::JS { code: _, input, .. } => 1 + input.depth(),
+        }
+    }
 }
 
 impl<L, F> Cost for AST<L, F>
@@ -673,11 +1257,65 @@ impl Display for AST<Lit, Fun> {
                 let b = args[1].clone();
                 write!(f, "({a} == {b})")
             }
+            AST::App {
+                fun: Fun::ParseInt,
+                args,
+            } => {
+                let x = args[0].clone();
+                write!(f, "int({x})")
+            }
+            AST::App {
+                fun: Fun::FormatInt,
+                args,
+            } if args[1] == AST::Lit(Lit::IntConst(0)) => {
+                let n = args[0].clone();
+                write!(f, "str({n})")
+            }
+            AST::App {
+                fun: Fun::FormatInt,
+                args,
+            } => {
+                let (n, width) = (args[0].clone(), args[1].clone());
+                write!(f, "str({n}, {width})")
+            }
+            AST::App {
+                fun: fun @ (Fun::Add | Fun::Sub | Fun::Mul | Fun::Div),
+                args,
+            } => {
+                let op = match fun {
+                    Fun::Add => "+",
+                    Fun::Sub => "-",
+                    Fun::Mul => "*",
+                    _ => "/",
+                };
+                let a = args[0].clone();
+                let b = args[1].clone();
+                write!(f, "({a} {op} {b})")
+            }
+            AST::App {
+                fun: Fun::Apply,
+                args,
+            } => {
+                // the lambda's input is a plain string, so every column is X
+                let (value, body) = (
+                    args[0].clone(),
+                    args[1].map_lits(&|l| match l {
+                        Lit::Input(_) => Lit::Input(0),
+                        _ => l.clone(),
+                    }),
+                );
+                write!(f, "(λX.{body})({value})")
+            }
+            AST::App { fun: Fun::If, args } => {
+                let (cond, then, els) = (args[0].clone(), args[1].clone(), args[2].clone());
+                write!(f, "({then} if {cond} else {els})")
+            }
             AST::Lit(Lit::StringConst(s)) => write!(f, "'{}'", s),
             AST::Lit(Lit::LocConst(n)) => write!(f, "{}", n),
             AST::Lit(Lit::BoolConst(b)) => write!(f, "{}", b),
+            AST::Lit(Lit::IntConst(n)) => write!(f, "{}", n),
             AST::Lit(Lit::LocEnd) => write!(f, "$"),
-            AST::Lit(Lit::Input) => write!(f, "X"),
+            AST::Lit(l @ (Lit::Input(_) | Lit::Tuple(_) | Lit::Token(_))) => write!(f, "{}", l),
             AST::JS { code, input, .. } => write!(f, "(lambda X: {})({})", code, input),
         }
     }
@@ -0,0 +1,731 @@
+use std::{collections::HashSet, sync::Arc};
+
+use itertools::{iproduct, Itertools};
+
+use super::bank::Bank;
+use super::dsl::{ASTOf, Dsl, StringDsl, VSAOf};
+use super::vsa::{self, Fun, Language, Lit};
+use super::{regex, AST, VSA};
+
+macro_rules! loc_pat {
+    () => {
+        AST::Lit(Lit::LocConst(_) | Lit::LocEnd)
+            | AST::App {
+                fun: Fun::Find | Fun::LocAdd | Fun::LocSub,
+                ..
+            }
+    };
+}
+
+pub struct WitnessCtx<'a, D: Dsl> {
+    pub inp: &'a D::Lit,
+    pub bank: &'a Bank<ASTOf<D>>,
+}
+
+impl<'a, D: Dsl> WitnessCtx<'a, D> {
+    // a subgoal for `learn` to fill in
+    pub fn hole(&self, goal: D::Lit) -> Arc<VSAOf<D>> {
+        Arc::new(vsa::VSA::Unlearned {
+            start: self.inp.clone(),
+            goal,
+            others: Vec::new(),
+        })
+    }
+
+    // a lambda's subgoal, to be learned for all of its examples at once
+    pub fn lambda_hole(&self, examples: &[(D::Lit, D::Lit)]) -> Arc<VSAOf<D>> {
+        Arc::new(vsa::VSA::Unlearned {
+            start: examples[0].0.clone(),
+            goal: examples[0].1.clone(),
+            others: examples[1..].to_vec(),
+        })
+    }
+
+    pub fn bank_matching(
+        &self,
+        p: fn(&ASTOf<D>) -> bool,
+    ) -> impl Iterator<Item = &ASTOf<D>> + Clone {
+        self.bank
+            .entries
+            .iter()
+            .flat_map(move |entry| entry.iter().filter(move |ast| p(ast)))
+    }
+}
+
+// Inverse semantics for one operator. Given the input and a goal output,
+// returns the VSAs that could produce the goal, with subgoals left as
+// `VSA::Unlearned` holes for `learn` to fill in.
+pub trait Witness<D: Dsl> {
+    fn name(&self) -> &'static str;
+
+    // the operators at the root of the VSAs it returns, if it builds any
+    fn operators(&self) -> Vec<D::Fun> {
+        Vec::new()
+    }
+
+    fn witness(&self, ctx: &WitnessCtx<D>, goal: &D::Lit) -> Vec<VSAOf<D>>;
+}
+
+// The witnesses `learn` tries, in order, each of which can be switched off.
+// Shared between clones, so a `SynthConfig` can take it to another thread.
+pub struct WitnessRegistry<D: Dsl> {
+    witnesses: Vec<(Arc<dyn Witness<D> + Send + Sync>, bool)>,
+}
+
+impl<D: Dsl> WitnessRegistry<D> {
+    pub fn new() -> Self {
+        WitnessRegistry {
+            witnesses: Vec::new(),
+        }
+    }
+
+    pub fn with(mut self, witness: impl Witness<D> + Send + Sync + 'static) -> Self {
+        self.register(witness);
+        self
+    }
+
+    pub fn register(&mut self, witness: impl Witness<D> + Send + Sync + 'static) {
+        self.witnesses.push((Arc::new(witness), true));
+    }
+
+    pub fn insert(&mut self, index: usize, witness: impl Witness<D> + Send + Sync + 'static) {
+        self.witnesses.insert(index, (Arc::new(witness), true));
+    }
+
+    pub fn remove(&mut self, name: &str) -> Option<Arc<dyn Witness<D> + Send + Sync>> {
+        let i = self.position(name)?;
+        Some(self.witnesses.remove(i).0)
+    }
+
+    pub fn set_enabled(&mut self, name: &str, enabled: bool) -> bool {
+        match self.witnesses.iter_mut().find(|(w, _)| w.name() == name) {
+            Some((_, e)) => {
+                *e = enabled;
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn move_to(&mut self, name: &str, index: usize) -> bool {
+        match self.position(name) {
+            Some(i) => {
+                let witness = self.witnesses.remove(i);
+                self.witnesses
+                    .insert(index.min(self.witnesses.len()), witness);
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = (&'static str, bool)> + '_ {
+        self.witnesses
+            .iter()
+            .map(|(w, enabled)| (w.name(), *enabled))
+    }
+
+    pub fn enabled(&self) -> impl Iterator<Item = &dyn Witness<D>> {
+        self.witnesses
+            .iter()
+            .filter(|(_, enabled)| *enabled)
+            .map(|(w, _)| w.as_ref() as &dyn Witness<D>)
+    }
+
+    // the enabled witnesses that can still build one of their operators, when
+    // `disabled` are switched off in the config
+    pub fn enabled_without<'a>(
+        &'a self,
+        disabled: &'a [D::Fun],
+    ) -> impl Iterator<Item = &'a dyn Witness<D>> {
+        self.enabled().filter(|witness| {
+            let ops = witness.operators();
+            ops.is_empty() || ops.iter().any(|op| !disabled.contains(op))
+        })
+    }
+
+    fn position(&self, name: &str) -> Option<usize> {
+        self.witnesses.iter().position(|(w, _)| w.name() == name)
+    }
+}
+
+impl<D: Dsl> Default for WitnessRegistry<D> {
+    fn default() -> Self {
+        D::witnesses()
+    }
+}
+
+impl<D: Dsl> Clone for WitnessRegistry<D> {
+    fn clone(&self) -> Self {
+        WitnessRegistry {
+            witnesses: self.witnesses.clone(),
+        }
+    }
+}
+
+impl<D: Dsl> std::fmt::Debug for WitnessRegistry<D> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_map().entries(self.names()).finish()
+    }
+}
+
+// witnesses are told apart by name
+impl<D: Dsl> PartialEq for WitnessRegistry<D> {
+    fn eq(&self, other: &Self) -> bool {
+        self.names().eq(other.names())
+    }
+}
+
+// TODO:
+// this makes it impossible to learn in one shot
+pub struct ConstWitness;
+
+impl Witness<StringDsl> for ConstWitness {
+    fn name(&self) -> &'static str {
+        "const"
+    }
+
+    fn witness(&self, _ctx: &WitnessCtx<StringDsl>, goal: &Lit) -> Vec<VSA> {
+        match goal {
+            Lit::StringConst(_) | Lit::BoolConst(_) | Lit::LocConst(_) | Lit::IntConst(_) => {
+                vec![VSA::singleton(AST::Lit(goal.clone()))]
+            }
+            _ => Vec::new(),
+        }
+    }
+}
+
+pub struct LocEndWitness;
+
+impl Witness<StringDsl> for LocEndWitness {
+    fn name(&self) -> &'static str {
+        "loc_end"
+    }
+
+    fn witness(&self, ctx: &WitnessCtx<StringDsl>, goal: &Lit) -> Vec<VSA> {
+        match (goal, ctx.inp) {
+            (Lit::LocConst(n), Lit::StringConst(inp_str)) if inp_str.chars().count() == *n => {
+                vec![VSA::singleton(AST::Lit(Lit::LocEnd))]
+            }
+            _ => Vec::new(),
+        }
+    }
+}
+
+pub struct EqualWitness;
+
+impl Witness<StringDsl> for EqualWitness {
+    fn name(&self) -> &'static str {
+        "equal"
+    }
+
+    fn operators(&self) -> Vec<Fun> {
+        vec![Fun::Equal]
+    }
+
+    fn witness(&self, ctx: &WitnessCtx<StringDsl>, goal: &Lit) -> Vec<VSA> {
+        if !matches!(goal, Lit::BoolConst(_)) {
+            return Vec::new();
+        }
+
+        let is_loc: fn(&AST) -> bool = |ast| matches!(ast, loc_pat!());
+        let s = iproduct!(ctx.bank_matching(is_loc), ctx.bank_matching(is_loc))
+            .map(|(lhs, rhs)| AST::App {
+                fun: Fun::Equal,
+                args: vec![lhs.clone(), rhs.clone()],
+            })
+            .map(Arc::new)
+            .collect();
+        vec![VSA::Leaf(s)]
+    }
+}
+
+// the input literal and text of each column of an input, a plain string is
+// a single column
+pub fn columns(inp: &Lit) -> Vec<(Lit, &str)> {
+    match inp {
+        Lit::StringConst(s) => vec![(Lit::Input(0), s.as_str())],
+        Lit::Tuple(cols) => cols
+            .iter()
+            .enumerate()
+            .filter_map(|(i, col)| match col {
+                Lit::StringConst(s) => Some((Lit::Input(i), s.as_str())),
+                _ => None,
+            })
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+// goal = prefix <> (X <> suffix) for every occurrence of a column X in the
+// goal
+//
+// the input is matched literally, a regex match wouldn't
+// necessarily be equal to X
+pub struct ConcatInputWitness;
+
+impl Witness<StringDsl> for ConcatInputWitness {
+    fn name(&self) -> &'static str {
+        "concat_input"
+    }
+
+    fn operators(&self) -> Vec<Fun> {
+        vec![Fun::Concat]
+    }
+
+    fn witness(&self, ctx: &WitnessCtx<StringDsl>, goal: &Lit) -> Vec<VSA> {
+        let Lit::StringConst(s) = goal else {
+            return Vec::new();
+        };
+
+        columns(ctx.inp)
+            .into_iter()
+            .filter(|(_, col)| s.contains(col))
+            .flat_map(|(input, col)| {
+                s.match_indices(col).map(move |(start, m)| {
+                    let end = start + m.len();
+                    let start_lit = Lit::StringConst(s[0..start].to_string());
+                    let end_lit = Lit::StringConst(s[end..].to_string());
+                    // TODO: maybe add a simplify function to the AST
+                    VSA::Join {
+                        op: Fun::Concat,
+                        children: vec![
+                            ctx.hole(start_lit.clone()),
+                            Arc::new(VSA::Join {
+                                op: Fun::Concat,
+                                children: vec![
+                                    Arc::new(VSA::singleton(AST::Lit(input.clone()))),
+                                    ctx.hole(end_lit.clone()),
+                                ],
+                                children_goals: vec![input.clone(), end_lit],
+                            }),
+                        ],
+                        children_goals: vec![start_lit, Lit::StringConst(s[start..].to_string())],
+                    }
+                })
+            })
+            .collect()
+    }
+}
+
+// goal = X[start..end] for the first occurrence of the goal in X
+pub struct SliceWitness;
+
+impl Witness<StringDsl> for SliceWitness {
+    fn name(&self) -> &'static str {
+        "slice"
+    }
+
+    fn operators(&self) -> Vec<Fun> {
+        vec![Fun::Slice]
+    }
+
+    fn witness(&self, ctx: &WitnessCtx<StringDsl>, goal: &Lit) -> Vec<VSA> {
+        match (goal, ctx.inp) {
+            (Lit::StringConst(s), Lit::StringConst(inp_str)) if inp_str.contains(s) => {
+                let start = vsa::char_pos(inp_str, inp_str.find(s).unwrap());
+                let end = start + s.chars().count();
+                let start_lit = Lit::LocConst(start);
+                let end_lit = Lit::LocConst(end);
+                vec![VSA::Join {
+                    op: Fun::Slice,
+                    children: vec![ctx.hole(start_lit.clone()), ctx.hole(end_lit.clone())],
+                    children_goals: vec![start_lit, end_lit],
+                }]
+            }
+            _ => Vec::new(),
+        }
+    }
+}
+
+// goal = (λX.body)(Xi) for every column Xi of an input with several that
+// the goal is a part of, ignoring case. The body is learned on just that
+// column, so it can slice and search it like a single input. Other goals are
+// split up by the concat witnesses first.
+pub struct ColumnWitness;
+
+impl Witness<StringDsl> for ColumnWitness {
+    fn name(&self) -> &'static str {
+        "column"
+    }
+
+    fn operators(&self) -> Vec<Fun> {
+        vec![Fun::Apply]
+    }
+
+    fn witness(&self, ctx: &WitnessCtx<StringDsl>, goal: &Lit) -> Vec<VSA> {
+        let (Lit::StringConst(s), Lit::Tuple(_)) = (goal, ctx.inp) else {
+            return Vec::new();
+        };
+
+        let lower = s.to_lowercase();
+        columns(ctx.inp)
+            .into_iter()
+            // a whole column is already in the bank
+            .filter(|(_, col)| !s.is_empty() && col != s)
+            .filter(|(_, col)| col.to_lowercase().contains(&lower))
+            .map(|(input, col)| {
+                let col = Lit::StringConst(col.to_string());
+                VSA::Join {
+                    op: Fun::Apply,
+                    children: vec![
+                        Arc::new(VSA::singleton(AST::Lit(input))),
+                        ctx.lambda_hole(&[(col.clone(), goal.clone())]),
+                    ],
+                    children_goals: vec![col, goal.clone()],
+                }
+            })
+            .collect()
+    }
+}
+
+// goal = sub.upper() or sub.lower() for every distinct substring of X that
+// matches the goal ignoring case, when the goal is all upper or lower case
+pub struct CaseWitness;
+
+impl Witness<StringDsl> for CaseWitness {
+    fn name(&self) -> &'static str {
+        "case"
+    }
+
+    fn operators(&self) -> Vec<Fun> {
+        vec![Fun::Uppercase, Fun::Lowercase]
+    }
+
+    fn witness(&self, ctx: &WitnessCtx<StringDsl>, goal: &Lit) -> Vec<VSA> {
+        let (Lit::StringConst(s), Lit::StringConst(inp_str)) = (goal, ctx.inp) else {
+            return Vec::new();
+        };
+
+        let (upper, lower) = (s.to_uppercase(), s.to_lowercase());
+        let ops = [(Fun::Uppercase, upper == *s), (Fun::Lowercase, lower == *s)];
+        // without cased letters there's nothing to convert
+        if upper == lower {
+            return Vec::new();
+        }
+
+        // case conversion keeps the number of chars for all but a few letters
+        let len = s.chars().count();
+        let bounds = inp_str
+            .char_indices()
+            .map(|(i, _)| i)
+            .chain([inp_str.len()])
+            .collect::<Vec<_>>();
+        let subs = bounds
+            .windows(len + 1)
+            .map(|w| &inp_str[w[0]..w[len]])
+            .filter(|sub| sub.to_lowercase() == lower)
+            .unique()
+            .collect::<Vec<_>>();
+
+        iproduct!(ops.into_iter().filter(|(_, applies)| *applies), subs)
+            .map(|((op, _), sub)| {
+                let sub = Lit::StringConst(sub.to_string());
+                VSA::Join {
+                    op,
+                    children: vec![ctx.hole(sub.clone())],
+                    children_goals: vec![sub],
+                }
+            })
+            .collect()
+    }
+}
+
+// the most ways to cut up the goal `ConcatMapWitness` tries per separator
+const MAX_CUTS: usize = 64;
+
+// goal = X.split(sep).concat_map(λX.body) for every separator in X, with the
+// goal cut into one non-empty piece per part of X. The body has to map every
+// part to its piece, so it's learned for all of them at once. Only cuts where
+// each piece shares a letter or digit with its part are tried, otherwise
+// there are far too many.
+pub struct ConcatMapWitness;
+
+impl Witness<StringDsl> for ConcatMapWitness {
+    fn name(&self) -> &'static str {
+        "concat_map"
+    }
+
+    fn operators(&self) -> Vec<Fun> {
+        vec![Fun::ConcatMap]
+    }
+
+    fn witness(&self, ctx: &WitnessCtx<StringDsl>, goal: &Lit) -> Vec<VSA> {
+        let (Lit::StringConst(s), Lit::StringConst(inp_str)) = (goal, ctx.inp) else {
+            return Vec::new();
+        };
+
+        let seps = inp_str.chars().filter(|c| !c.is_alphanumeric()).unique();
+        seps.flat_map(|sep| {
+            let sep = Lit::StringConst(sep.to_string());
+            let parts = Fun::ConcatMap.lambda_inputs(std::slice::from_ref(&sep), ctx.inp);
+            cuts(s, &parts)
+                .into_iter()
+                .map(|pieces| {
+                    let examples = parts.iter().cloned().zip(pieces).collect::<Vec<_>>();
+                    VSA::Join {
+                        op: Fun::ConcatMap,
+                        children: vec![
+                            Arc::new(VSA::singleton(AST::Lit(sep.clone()))),
+                            ctx.lambda_hole(&examples),
+                        ],
+                        children_goals: vec![sep.clone(), examples[0].1.clone()],
+                    }
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect()
+    }
+}
+
+// ways to cut `s` into a piece per part, see `ConcatMapWitness`
+fn cuts(s: &str, parts: &[Lit]) -> Vec<Vec<Lit>> {
+    fn go(
+        s: &str,
+        bounds: &[usize],
+        parts: &[String],
+        pieces: &mut Vec<Lit>,
+        res: &mut Vec<Vec<Lit>>,
+    ) {
+        let Some((part, rest)) = parts.split_first() else {
+            res.push(pieces.clone());
+            return;
+        };
+        // the last piece takes what's left, the others leave a char for each
+        // of the following pieces
+        let ends = if rest.is_empty() {
+            bounds.len() - 1..bounds.len()
+        } else {
+            1..bounds.len().saturating_sub(rest.len())
+        };
+        for end in ends {
+            if res.len() >= MAX_CUTS {
+                return;
+            }
+            let piece = &s[bounds[0]..bounds[end]];
+            let shared = piece
+                .to_lowercase()
+                .chars()
+                .any(|c| c.is_alphanumeric() && part.contains(c));
+            if shared {
+                pieces.push(Lit::StringConst(piece.to_string()));
+                go(s, &bounds[end..], rest, pieces, res);
+                pieces.pop();
+            }
+        }
+    }
+
+    let parts = parts
+        .iter()
+        .map(|part| match part {
+            Lit::StringConst(part) => Some(part.to_lowercase()),
+            _ => None,
+        })
+        .collect::<Option<Vec<_>>>();
+    let bounds = s
+        .char_indices()
+        .map(|(i, _)| i)
+        .chain([s.len()])
+        .collect::<Vec<_>>();
+    let mut res = Vec::new();
+    match parts {
+        Some(parts) if parts.len() > 1 && parts.len() < bounds.len() => {
+            go(s, &bounds, &parts, &mut Vec::new(), &mut res)
+        }
+        _ => {}
+    }
+    res
+}
+
+// the numbers in a string with the text they're parsed from, in order
+fn numbers(s: &str) -> Vec<(i64, &str)> {
+    regex("-?\\d+")
+        .unwrap()
+        .find_iter(s)
+        .filter_map(|m| Some((m.as_str().parse().ok()?, m.as_str())))
+        .collect()
+}
+
+// goal = str(n, width) when the goal is a number, for every width that
+// pads it right
+pub struct FormatIntWitness;
+
+impl Witness<StringDsl> for FormatIntWitness {
+    fn name(&self) -> &'static str {
+        "format_int"
+    }
+
+    fn operators(&self) -> Vec<Fun> {
+        vec![Fun::FormatInt]
+    }
+
+    fn witness(&self, ctx: &WitnessCtx<StringDsl>, goal: &Lit) -> Vec<VSA> {
+        let Lit::StringConst(s) = goal else {
+            return Vec::new();
+        };
+        let Ok(n) = s.parse::<i64>() else {
+            return Vec::new();
+        };
+
+        let widths = (0..=s.len())
+            .filter(|width| format!("{n:0width$}") == *s)
+            .map(|width| Arc::new(AST::Lit(Lit::IntConst(width as i64))))
+            .collect::<HashSet<_>>();
+        if widths.is_empty() {
+            return Vec::new();
+        }
+        vec![VSA::Join {
+            op: Fun::FormatInt,
+            children: vec![ctx.hole(Lit::IntConst(n)), Arc::new(VSA::Leaf(widths))],
+            children_goals: vec![Lit::IntConst(n), Lit::IntConst(s.len() as i64)],
+        }]
+    }
+}
+
+// goal = int(s) for every distinct number s in X with the goal's value
+pub struct ParseIntWitness;
+
+impl Witness<StringDsl> for ParseIntWitness {
+    fn name(&self) -> &'static str {
+        "parse_int"
+    }
+
+    fn operators(&self) -> Vec<Fun> {
+        vec![Fun::ParseInt]
+    }
+
+    fn witness(&self, ctx: &WitnessCtx<StringDsl>, goal: &Lit) -> Vec<VSA> {
+        let (Lit::IntConst(n), Lit::StringConst(inp_str)) = (goal, ctx.inp) else {
+            return Vec::new();
+        };
+
+        numbers(inp_str)
+            .into_iter()
+            .filter(|(m, _)| m == n)
+            .map(|(_, text)| text)
+            .unique()
+            .map(|text| {
+                let text = Lit::StringConst(text.to_string());
+                VSA::Join {
+                    op: Fun::ParseInt,
+                    children: vec![ctx.hole(text.clone())],
+                    children_goals: vec![text],
+                }
+            })
+            .collect()
+    }
+}
+
+// goal = x op c for every number x in X and operator with a constant c that
+// gives the goal, other than the ones that leave x as it is. The goal for the
+// left side is always a number from X, so there's only ever a few of them.
+pub struct ArithWitness;
+
+impl Witness<StringDsl> for ArithWitness {
+    fn name(&self) -> &'static str {
+        "arith"
+    }
+
+    fn operators(&self) -> Vec<Fun> {
+        vec![Fun::Add, Fun::Sub, Fun::Mul, Fun::Div]
+    }
+
+    fn witness(&self, ctx: &WitnessCtx<StringDsl>, goal: &Lit) -> Vec<VSA> {
+        let (Lit::IntConst(n), Lit::StringConst(inp_str)) = (goal, ctx.inp) else {
+            return Vec::new();
+        };
+        let n = *n;
+
+        let xs = numbers(inp_str)
+            .into_iter()
+            .map(|(x, _)| x)
+            .unique()
+            .collect::<Vec<_>>();
+        iproduct!(xs, [Fun::Add, Fun::Sub, Fun::Mul, Fun::Div])
+            .filter_map(|(x, op)| {
+                let c = match op {
+                    Fun::Add => n.checked_sub(x).filter(|c| *c != 0)?,
+                    Fun::Sub => x.checked_sub(n).filter(|c| *c != 0)?,
+                    Fun::Mul if n.checked_rem(x)? == 0 => n.checked_div(x)?,
+                    Fun::Div => x.checked_div(n)?,
+                    _ => return None,
+                };
+                let (x, c) = (Lit::IntConst(x), Lit::IntConst(c));
+                // c can still be off for division or make the op a no-op
+                let works = op.eval(&[x.clone(), c.clone()], ctx.inp) == *goal;
+                let no_op = matches!(op, Fun::Mul | Fun::Div) && c == Lit::IntConst(1);
+                (works && !no_op).then(|| VSA::Join {
+                    op,
+                    children: vec![
+                        ctx.hole(x.clone()),
+                        Arc::new(VSA::singleton(AST::Lit(c.clone()))),
+                    ],
+                    children_goals: vec![x, c],
+                })
+            })
+            .collect()
+    }
+}
+
+// goal = goal[..i] <> goal[i..] for every split point, when no column of the
+// input is part of the goal or the other way round
+pub struct ConcatSplitWitness;
+
+impl Witness<StringDsl> for ConcatSplitWitness {
+    fn name(&self) -> &'static str {
+        "concat_split"
+    }
+
+    fn operators(&self) -> Vec<Fun> {
+        vec![Fun::Concat]
+    }
+
+    fn witness(&self, ctx: &WitnessCtx<StringDsl>, goal: &Lit) -> Vec<VSA> {
+        let cols = columns(ctx.inp);
+        match goal {
+            Lit::StringConst(s)
+                if !cols.is_empty()
+                    && cols
+                        .iter()
+                        .all(|(_, col)| !col.contains(s.as_str()) && !s.contains(col)) =>
+            {
+                let set = s
+                    .char_indices()
+                    .skip(1)
+                    .map(|(i, _)| {
+                        let lhs = Lit::StringConst(s[0..i].to_string());
+                        let rhs = Lit::StringConst(s[i..].to_string());
+                        VSA::Join {
+                            op: Fun::Concat,
+                            children: vec![ctx.hole(lhs.clone()), ctx.hole(rhs.clone())],
+                            children_goals: vec![lhs, rhs],
+                        }
+                    })
+                    .map(Arc::new)
+                    .collect();
+
+                vec![VSA::Union(set)]
+            }
+            _ => Vec::new(),
+        }
+    }
+}
+
+// TODO: figure out the index
+// (Lit::LocConst(n), Lit::StringConst(s)) if s.chars().nth(*n).is_some_and(|ch| ch == ' ') => {
+//     let lhs = Arc::new(VSA::singleton(AST::Lit(Lit::Input)));
+//     let space = cache.get(&Lit::StringConst(" ".to_string())).unwrap().clone();
+//     let wb = cache.get(&Lit::StringConst("\\b".to_string())).unwrap().clone();
+
+//     unifier.push(VSA::Join {
+//         op: Fun::Find,
+//         children: vec![lhs.clone(), space],
+//     });
+
+//     if s.chars().nth(n - 1).is_some_and(|ch| ch.is_alphanumeric()) {
+//         unifier.push(VSA::Join {
+//             op: Fun::Find,
+//             children: vec![lhs, wb],
+//         });
+//     }
+// }
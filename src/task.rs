@@ -0,0 +1,306 @@
+use std::path::Path;
+
+use serde::Deserialize;
+
+use crate::synth::config::{ConfigRepr, SynthConfig};
+use crate::synth::dsl::StringDsl;
+use crate::synth::vsa::Lit;
+
+// A synthesis task, loaded from either
+//
+// JSON:
+// {
+//     "examples": [
+//         { "input": "First Last", "output": "F.L." },
+//         { "input": "abc", "output": { "loc": 2 } },
+//         { "input": "abc", "output": true }
+//     ],
+//     "other_inputs": ["Another Name"],
+//     "settings": { "learn_depth": 1 },
+//     "config": { "max_size": 6 }
+// }
+//
+// where "config" has the same format as a `SynthConfig` file. An input with
+// several columns is a list of strings, e.g. "input": ["First", "Last"], and
+// "columns": ["first", "last"] optionally names them.
+//
+// or a CSV of input,output. With more than two columns the last one is the
+// output and the others are the input's. An optional header row names the
+// columns, and the output column can be typed as `output:loc` or `output:bool`.
+// Rows with an empty output are unlabeled inputs.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Task {
+    pub examples: Vec<(Lit, Lit)>,
+    pub other_inputs: Vec<Lit>,
+    // the names of the input's columns, if it has several and they're named
+    pub columns: Vec<String>,
+    pub settings: Settings,
+    pub config: SynthConfig<StringDsl>,
+}
+
+// max_size and search_depth used to be settings, they're in the config now
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Settings {
+    pub learn_depth: usize,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings { learn_depth: 1 }
+    }
+}
+
+#[derive(Debug)]
+pub enum TaskError {
+    Io(std::io::Error),
+    Json(serde_json::Error),
+    Csv(csv::Error),
+    Format(String),
+}
+
+impl std::fmt::Display for TaskError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TaskError::Io(e) => write!(f, "could not read file: {e}"),
+            TaskError::Json(e) => write!(f, "invalid JSON: {e}"),
+            TaskError::Csv(e) => write!(f, "invalid CSV: {e}"),
+            TaskError::Format(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl std::error::Error for TaskError {}
+
+impl From<std::io::Error> for TaskError {
+    fn from(e: std::io::Error) -> Self {
+        TaskError::Io(e)
+    }
+}
+
+impl From<serde_json::Error> for TaskError {
+    fn from(e: serde_json::Error) -> Self {
+        TaskError::Json(e)
+    }
+}
+
+impl From<csv::Error> for TaskError {
+    fn from(e: csv::Error) -> Self {
+        TaskError::Csv(e)
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum OutputRepr {
+    Str(String),
+    Bool(bool),
+    Loc { loc: usize },
+}
+
+impl From<OutputRepr> for Lit {
+    fn from(out: OutputRepr) -> Self {
+        match out {
+            OutputRepr::Str(s) => Lit::StringConst(s),
+            OutputRepr::Bool(b) => Lit::BoolConst(b),
+            OutputRepr::Loc { loc } => Lit::LocConst(loc),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum InputRepr {
+    Str(String),
+    Columns(Vec<String>),
+}
+
+impl From<InputRepr> for Lit {
+    fn from(inp: InputRepr) -> Self {
+        match inp {
+            InputRepr::Str(s) => Lit::StringConst(s),
+            InputRepr::Columns(cols) => columns(cols),
+        }
+    }
+}
+
+// a multi column input, a single column is just a string
+fn columns(mut cols: Vec<String>) -> Lit {
+    if cols.len() == 1 {
+        Lit::StringConst(cols.remove(0))
+    } else {
+        Lit::Tuple(cols.into_iter().map(Lit::StringConst).collect())
+    }
+}
+
+#[derive(Deserialize)]
+struct ExampleRepr {
+    input: InputRepr,
+    output: OutputRepr,
+}
+
+#[derive(Deserialize)]
+struct TaskRepr {
+    examples: Vec<ExampleRepr>,
+    #[serde(default)]
+    other_inputs: Vec<InputRepr>,
+    #[serde(default)]
+    columns: Vec<String>,
+    #[serde(default)]
+    settings: Settings,
+    #[serde(default)]
+    config: ConfigRepr,
+}
+
+#[derive(Clone, Copy)]
+enum OutputTyp {
+    Str,
+    Loc,
+    Bool,
+}
+
+impl OutputTyp {
+    fn parse(&self, s: &str) -> Result<Lit, TaskError> {
+        match self {
+            OutputTyp::Str => Ok(Lit::StringConst(s.to_string())),
+            OutputTyp::Loc => s
+                .trim()
+                .parse()
+                .map(Lit::LocConst)
+                .map_err(|_| TaskError::Format(format!("expected a location, found {s:?}"))),
+            OutputTyp::Bool => s
+                .trim()
+                .parse()
+                .map(Lit::BoolConst)
+                .map_err(|_| TaskError::Format(format!("expected a bool, found {s:?}"))),
+        }
+    }
+}
+
+impl Task {
+    pub fn new(examples: Vec<(Lit, Lit)>) -> Self {
+        Task {
+            examples,
+            other_inputs: Vec::new(),
+            columns: Vec::new(),
+            settings: Settings::default(),
+            config: SynthConfig::default(),
+        }
+    }
+
+    pub fn from_json(src: &str) -> Result<Self, TaskError> {
+        let repr: TaskRepr = serde_json::from_str(src)?;
+        Task {
+            examples: repr
+                .examples
+                .into_iter()
+                .map(|ex| (ex.input.into(), ex.output.into()))
+                .collect(),
+            other_inputs: repr.other_inputs.into_iter().map(Lit::from).collect(),
+            columns: repr.columns,
+            settings: repr.settings,
+            config: repr.config.try_into()?,
+        }
+        .validated()
+    }
+
+    pub fn from_csv(src: &str) -> Result<Self, TaskError> {
+        let mut reader = csv::ReaderBuilder::new()
+            .has_headers(false)
+            .flexible(true)
+            .from_reader(src.as_bytes());
+
+        let mut task = Task::new(Vec::new());
+        let mut out_typ = OutputTyp::Str;
+        let mut width = None;
+        for (i, record) in reader.records().enumerate() {
+            let record = record?;
+            // a lone input has an empty output
+            let len = record.len().max(2);
+            let cols = (0..len - 1)
+                .map(|col| record.get(col).unwrap_or_default().to_string())
+                .collect::<Vec<_>>();
+            let out = record.get(len - 1).unwrap_or_default();
+
+            let is_header = cols[0].trim().eq_ignore_ascii_case("input")
+                || out.trim().to_ascii_lowercase().starts_with("output");
+            if i == 0 && is_header {
+                out_typ = match out.trim() {
+                    "output:loc" => OutputTyp::Loc,
+                    "output:bool" => OutputTyp::Bool,
+                    _ => OutputTyp::Str,
+                };
+                if cols.len() > 1 {
+                    task.columns = cols.iter().map(|col| col.trim().to_string()).collect();
+                }
+                width = Some(len);
+                continue;
+            }
+
+            match width {
+                Some(width) if width != len => {
+                    return Err(TaskError::Format(format!(
+                        "row {} has {} columns, expected {}",
+                        i + 1,
+                        record.len(),
+                        width
+                    )));
+                }
+                _ => width = Some(len),
+            }
+
+            let inp = columns(cols);
+            if out.is_empty() {
+                task.other_inputs.push(inp);
+            } else {
+                task.examples.push((inp, out_typ.parse(out)?));
+            }
+        }
+
+        task.validated()
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, TaskError> {
+        let path = path.as_ref();
+        let src = std::fs::read_to_string(path)?;
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("csv") => Task::from_csv(&src),
+            _ => Task::from_json(&src),
+        }
+    }
+
+    // how many columns the inputs have
+    pub fn num_columns(&self) -> usize {
+        match &self.examples[0].0 {
+            Lit::Tuple(cols) => cols.len(),
+            _ => 1,
+        }
+    }
+
+    fn validated(self) -> Result<Self, TaskError> {
+        if self.examples.is_empty() {
+            return Err(TaskError::Format("no labeled examples".to_string()));
+        }
+
+        let num_columns = self.num_columns();
+        let inputs = self.examples.iter().map(|(inp, _)| inp);
+        for inp in inputs.chain(&self.other_inputs) {
+            let n = match inp {
+                Lit::Tuple(cols) => cols.len(),
+                _ => 1,
+            };
+            if n != num_columns {
+                return Err(TaskError::Format(format!(
+                    "input {inp} has {n} columns, expected {num_columns}"
+                )));
+            }
+        }
+        if !self.columns.is_empty() && self.columns.len() != num_columns {
+            return Err(TaskError::Format(format!(
+                "{} column names for {num_columns} columns",
+                self.columns.len()
+            )));
+        }
+        Ok(self)
+    }
+}
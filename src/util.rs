@@ -1,11 +1,11 @@
 use egui_macroquad::egui::{self, Id};
 use egui_macroquad::macroquad::prelude::*;
 
-use std::rc::Rc;
+use std::sync::Arc;
 
 // potentially changes?
-pub fn rc_to_id<T>(rc: Rc<T>) -> Id {
-    let ptr = Rc::into_raw(rc);
+pub fn arc_to_id<T>(arc: Arc<T>) -> Id {
+    let ptr = Arc::into_raw(arc);
     Id::new(ptr as usize)
 }
 
@@ -1,15 +1,25 @@
-use crate::synth::vsa::*;
+use interactive_vsa::synth::vsa::*;
+use interactive_vsa::{
+    distinguishing_input, Answer, Budget, Exhausted, Progress, Question, Ranked, StringDsl,
+    SynthConfig, Task,
+};
 use egui_macroquad::egui::{self, Area, Context, Id, InnerResponse, Rect};
 use egui_macroquad::macroquad::prelude::*;
 
-use crate::util::{rc_to_id, vec2pos};
+use crate::util::{arc_to_id, vec2pos};
 
-use std::rc::Rc;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{Receiver, TryRecvError};
+use std::sync::Arc;
 
 const ARROW_ORDER: egui::layers::Order = egui::layers::Order::Middle;
 
+// the best programs shown so far and the rest of the ranking
+type BestPrograms = (Ranked<Lit, Fun>, Vec<AST<Lit, Fun>>);
+
 pub struct RichVSA {
-    pub vsa: Rc<VSA<Lit, Fun>>,
+    pub vsa: Arc<VSA<Lit, Fun>>,
     pub input: Lit,
     pub other_inputs: Vec<(Lit, Option<Lit>)>,
     pub goal: Lit,
@@ -19,11 +29,50 @@ pub struct RichVSA {
     pub children: Vec<RichVSA>,
     pub drag: Option<Vec2>,
     pub editable: bool,
+    pub stopped: Option<Exhausted>,
+    pub job: Option<LearnJob>,
+    pub best: Option<BestPrograms>,
+    // None until asked for, then the input to ask about if there is one
+    pub question: Option<Option<Question<StringDsl>>>,
+}
+
+// A Learn running on a worker thread. The result is sent back once it's done
+// and picked up by `RichVSA::draw`.
+pub struct LearnJob {
+    receiver: Receiver<(VSA<Lit, Fun>, Option<Exhausted>)>,
+    cancel: Arc<AtomicBool>,
+    progress: Arc<Progress>,
+}
+
+impl LearnJob {
+    fn spawn(
+        budget: Budget,
+        learn: impl FnOnce(&Budget) -> VSA<Lit, Fun> + Send + 'static,
+    ) -> Self {
+        let (sender, receiver) = std::sync::mpsc::channel();
+        let job = LearnJob {
+            receiver,
+            cancel: budget.cancel_flag(),
+            progress: budget.progress(),
+        };
+
+        let run = move || {
+            let vsa = learn(&budget);
+            let _ = sender.send((vsa, budget.exhausted()));
+        };
+        // no threads on the web, so it still blocks there
+        #[cfg(not(target_arch = "wasm32"))]
+        std::thread::spawn(run);
+        #[cfg(target_arch = "wasm32")]
+        run();
+
+        job
+    }
 }
 
 impl RichVSA {
     pub fn new(
-        vsa: Rc<VSA<Lit, Fun>>,
+        vsa: Arc<VSA<Lit, Fun>>,
         input: Lit,
         goal: Lit,
         pos: Vec2,
@@ -50,26 +99,35 @@ impl RichVSA {
                     .collect()
             }
             VSA::Join {
+                op,
                 children,
                 children_goals,
-                ..
             } => children
                 .iter()
                 .zip(children_goals.iter())
                 .enumerate()
                 .map(|(i, (vsa, goal))| {
+                    // a lambda is shown on the first of its inputs, with the
+                    // others to try it on
+                    let (input, other_inps) = if op.is_lambda(i) {
+                        let mut inputs = op.lambda_inputs(&children_goals[..i], &input).into_iter();
+                        let first = inputs.next().unwrap_or_else(|| input.clone());
+                        (first, inputs.map(|inp| (inp, None)).collect())
+                    } else {
+                        (input.clone(), other_inps.clone())
+                    };
                     RichVSA::new(
                         vsa.clone(),
-                        input.clone(),
+                        input,
                         goal.clone(),
                         pos + Vec2::new(x_offs * i as f32, y_offs),
-                        other_inps.clone(),
+                        other_inps,
                     )
                 })
                 .collect(),
         };
 
-        let id = rc_to_id(vsa.clone());
+        let id = arc_to_id(vsa.clone());
         let area = Area::new("vsa").id(id).default_pos(vec2pos(pos));
 
         Self {
@@ -83,6 +141,32 @@ impl RichVSA {
             children,
             drag: None,
             editable: false,
+            stopped: None,
+            job: None,
+            best: None,
+            question: None,
+        }
+    }
+
+    pub fn from_task(task: &Task, pos: Vec2) -> Self {
+        let (input, goal) = task.examples[0].clone();
+        let other_inps = task.examples[1..]
+            .iter()
+            .map(|(inp, out)| (inp.clone(), Some(out.clone())))
+            .chain(task.other_inputs.iter().map(|inp| (inp.clone(), None)))
+            .collect();
+        let vsa = Arc::new(VSA::Unlearned {
+            start: input.clone(),
+            goal: goal.clone(),
+            others: Vec::new(),
+        });
+
+        let rich_vsa = RichVSA::new(vsa, input, goal, pos, other_inps);
+        // the editor only knows how to edit string examples
+        if matches!(rich_vsa.goal, Lit::StringConst(_)) {
+            rich_vsa.editable()
+        } else {
+            rich_vsa
         }
     }
 
@@ -95,7 +179,7 @@ impl RichVSA {
 
     #[inline(always)]
     pub fn id(&self) -> Id {
-        rc_to_id(self.vsa.clone())
+        arc_to_id(self.vsa.clone())
     }
 
     pub fn set_vsa_style(ui: &mut egui::Ui) {
@@ -109,13 +193,30 @@ impl RichVSA {
         &mut self,
         labels: bool,
         learn_depth: usize,
-        search_depth: usize,
+        config: &SynthConfig<StringDsl>,
         egui_ctx: &Context,
     ) {
         let learn_pos = self.rect(egui_ctx).map(|r| {
             let egui::Pos2 { x, y } = r.left_top();
             vec2(x, y)
         });
+
+        let finished = self
+            .job
+            .as_ref()
+            .and_then(|job| match job.receiver.try_recv() {
+                Ok(learned) => Some(Some(learned)),
+                Err(TryRecvError::Empty) => None,
+                // the worker panicked, leave the node unlearned
+                Err(TryRecvError::Disconnected) => Some(None),
+            });
+        if let Some(learned) = finished {
+            self.job = None;
+            if let Some((new_vsa, stopped)) = learned {
+                self.replace_vsa(new_vsa, learn_pos.unwrap());
+                self.stopped = stopped;
+            }
+        }
         match self.vsa.as_ref() {
             VSA::Leaf(asts) => {
                 let sorted_asts = {
@@ -123,12 +224,19 @@ impl RichVSA {
                     asts.sort_by_key(|ast| ast.size());
                     asts
                 };
+                let stopped = self.stopped;
                 self.area.show(egui_ctx, |ui| {
                     Self::set_vsa_style(ui);
                     if labels {
                         ui.label("Leaf");
                         ui.label(format!("{} → {}", self.input, self.goal));
                     }
+                    Self::draw_stopped(stopped, ui);
+                    if asts.is_empty() {
+                        ui.label("No program found");
+                    } else {
+                        ui.label(format!("{} programs", asts.len()));
+                    }
                     let selected_ast = sorted_asts.iter().find(|ast| {
                         ui.horizontal(|ui| {
                             ui.label(format!("{}", ast));
@@ -139,7 +247,7 @@ impl RichVSA {
                     if let Some(ast) = selected_ast {
                         let unwrapped_ast = ast.as_ref().clone();
                         let new_vsa = VSA::singleton(unwrapped_ast);
-                        let self_mut = Rc::as_ptr(&self.vsa) as *mut _;
+                        let self_mut = Arc::as_ptr(&self.vsa) as *mut _;
                         // Safety: probably
                         unsafe { std::ptr::write(self_mut, new_vsa) };
                         self.children.clear();
@@ -147,28 +255,47 @@ impl RichVSA {
                 });
             }
             VSA::Union(_) => {
-                let InnerResponse { response, .. } = self.area.show(egui_ctx, |ui| {
+                let InnerResponse {
+                    inner: picked,
+                    response,
+                } = self.area.show(egui_ctx, |ui| {
                     Self::set_vsa_style(ui);
                     if labels {
                         ui.label("Union");
                     }
                     ui.label(format!("{} → {}", self.input, self.goal));
-                    Self::draw_other_inps(&mut self.other_inputs, ui);
+                    ui.label(self.vsa.stats().to_string());
+                    Self::draw_stopped(self.stopped, ui);
+                    Self::draw_other_inps(&mut self.other_inputs, num_columns(&self.input), ui);
+                    Self::draw_best(&mut self.best, &self.vsa, &self.other_inputs, ui);
+                    Self::draw_question(
+                        &mut self.question,
+                        &self.vsa,
+                        &self.input,
+                        &self.other_inputs,
+                        ui,
+                    )
                 });
+                if let Some((input, answer)) = picked {
+                    self.answer(input, answer, learn_pos.unwrap());
+                }
                 let edrag = response
                     .dragged_by(egui::PointerButton::Primary)
                     .then(|| response.drag_delta());
                 self.drag = edrag.map(|drag| Vec2::new(drag.x, drag.y));
                 let id = self.id();
                 for vsa in &mut self.children {
-                    vsa.draw(labels, learn_depth, search_depth, egui_ctx);
+                    vsa.draw(labels, learn_depth, config, egui_ctx);
                     draw_area_arrows(id, vsa.id(), egui_ctx);
                 }
             }
             VSA::Join {
                 op, children_goals, ..
             } => {
-                let InnerResponse { response, .. } = self.area.show(egui_ctx, |ui| {
+                let InnerResponse {
+                    inner: picked,
+                    response,
+                } = self.area.show(egui_ctx, |ui| {
                     Self::set_vsa_style(ui);
                     if labels {
                         ui.label(format!("{:?} Join", op));
@@ -177,179 +304,139 @@ impl RichVSA {
 
                     let args = children_goals
                         .iter()
-                        .map(|goal| format!("{}", goal))
+                        .enumerate()
+                        .map(|(i, goal)| {
+                            if op.is_lambda(i) {
+                                format!("λX → {}", goal)
+                            } else {
+                                format!("{}", goal)
+                            }
+                        })
                         .collect::<Vec<_>>()
                         .join(", ");
                     ui.label(format!("{:?}({})", op, args));
+                    for i in (0..children_goals.len()).filter(|i| op.is_lambda(*i)) {
+                        let inputs = op
+                            .lambda_inputs(&children_goals[..i], &self.input)
+                            .iter()
+                            .map(|inp| format!("{}", inp))
+                            .collect::<Vec<_>>()
+                            .join(", ");
+                        ui.label(format!("λX over {}", inputs));
+                    }
+                    Self::draw_stopped(self.stopped, ui);
                     // Self::draw_other_inps(&mut self.other_inputs, ui);
+                    Self::draw_best(&mut self.best, &self.vsa, &self.other_inputs, ui);
+                    Self::draw_question(
+                        &mut self.question,
+                        &self.vsa,
+                        &self.input,
+                        &self.other_inputs,
+                        ui,
+                    )
                 });
+                if let Some((input, answer)) = picked {
+                    self.answer(input, answer, learn_pos.unwrap());
+                }
                 let edrag = response
                     .dragged_by(egui::PointerButton::Primary)
                     .then(|| response.drag_delta());
                 self.drag = edrag.map(|drag| Vec2::new(drag.x, drag.y));
                 let id = self.id();
                 for vsa in self.children.iter_mut() {
-                    vsa.draw(labels, learn_depth, search_depth, egui_ctx);
+                    vsa.draw(labels, learn_depth, config, egui_ctx);
                     draw_area_arrows(id, vsa.id(), egui_ctx);
                 }
             }
-            VSA::Unlearned { start, goal } => {
+            VSA::Unlearned {
+                start,
+                goal,
+                others,
+            } => {
                 self.area.show(egui_ctx, |ui| {
                     Self::set_vsa_style(ui);
                     ui.label("Unlearned");
                     if self.editable {
+                        // every input has the same columns
                         ui.horizontal(|ui| {
-                            let mut inp_str = match &self.input {
-                                Lit::StringConst(s) => s.clone(),
-                                _ => "".to_string(),
+                            let columns = num_columns(&self.input);
+                            let add = ui.button("Add Column").clicked();
+                            let remove = columns > 1 && ui.button("Remove Column").clicked();
+                            let resized = if add {
+                                Some(columns + 1)
+                            } else if remove {
+                                Some(columns - 1)
+                            } else {
+                                None
                             };
+                            if let Some(columns) = resized {
+                                set_columns(&mut self.input, columns);
+                                for (inp, _) in self.other_inputs.iter_mut() {
+                                    set_columns(inp, columns);
+                                }
+                            }
+                        });
+                        ui.horizontal(|ui| {
                             let mut goal_str = match &self.goal {
                                 Lit::StringConst(s) => s.clone(),
                                 _ => "".to_string(),
                             };
-                            ui.text_edit_singleline(&mut inp_str);
+                            Self::edit_input(&mut self.input, ui);
                             ui.label("→");
                             ui.text_edit_singleline(&mut goal_str);
-                            self.input = Lit::StringConst(inp_str);
                             self.goal = Lit::StringConst(goal_str);
                             let new_vsa = VSA::Unlearned {
                                 start: self.input.clone(),
                                 goal: self.goal.clone(),
+                                others: Vec::new(),
                             };
-                            let self_mut = Rc::as_ptr(&self.vsa) as *mut VSA<Lit, Fun>;
+                            let self_mut = Arc::as_ptr(&self.vsa) as *mut VSA<Lit, Fun>;
                             // Safety: probably
                             unsafe { std::ptr::write(self_mut, new_vsa) };
                         });
                     } else {
                         ui.label(format!("{} → {}", start, goal));
                     }
-                    Self::draw_other_inps(&mut self.other_inputs, ui);
-                    if ui.button("Learn").clicked() {
-                        self.editable = false;
-
-                        let chars = {
-                            let start_chars = if let Lit::StringConst(s) = &start {
-                                s.chars()
-                            } else {
-                                "".chars()
-                            };
-                            let goal_chars = if let Lit::StringConst(s) = &goal {
-                                s.chars()
-                            } else {
-                                "".chars()
-                            };
-                            start_chars
-                                .chain(goal_chars)
-                                .map(|c| Lit::StringConst(c.to_string()))
-                                .collect::<Vec<_>>()
-                        };
-
-                        use std::collections::HashMap;
-                        let mut all_cache = HashMap::new();
-                        let mut bank = crate::synth::bank::Bank::new();
-                        let mut regex_bank = crate::synth::bank::Bank::new();
-
-                        let num_examples = 1 + self.other_inputs.len();
-
-                        for prim in [
-                            Lit::Input,
-                            Lit::StringConst("".to_string()),
-                            Lit::StringConst(" ".to_string()),
-                            Lit::StringConst(".".to_string()),
-                            Lit::LocConst(0),
-                            Lit::LocConst(1),
-                            Lit::LocEnd,
-                        ]
-                        .into_iter()
-                        // .chain(chars.clone().into_iter())
-                        {
-                            bank.size_mut(1).push(AST::Lit(prim.clone()));
-                            all_cache.insert(
-                                std::iter::repeat(prim.clone()).take(num_examples).collect(),
-                                Rc::new(VSA::singleton(AST::Lit(prim.clone()))),
-                            );
-                        }
-
-                        for prim in [
-                            Lit::StringConst("\\d".to_string()),
-                            Lit::StringConst("\\b".to_string()),
-                            Lit::StringConst("[a-z]".to_string()),
-                            Lit::StringConst("[A-Z]".to_string()),
-                        ]
-                        .into_iter()
-                        // .chain(chars.into_iter())
-                        {
-                            regex_bank.size_mut(1).push(AST::Lit(prim.clone()));
-                        }
-
-                        let bottom_up_inps = std::iter::once(start.clone())
-                            .chain(self.other_inputs.iter().map(|(inp, _)| inp.clone()))
-                            .collect::<Vec<_>>();
-                        for i in 1..=search_depth {
-                            crate::synth::bottom_up(
-                                bottom_up_inps.iter(),
-                                // std::iter::once(&start.clone()),
-                                i,
-                                &mut all_cache,
-                                &mut bank,
-                                &mut regex_bank,
-                                false,
-                            );
-                        }
-                        // dbg!(&bank);
-
-                        let complete_other_inps = self.other_inputs.iter().enumerate().filter_map(|(i, (inp, out))| {
-                            out.clone().map(|out| (i+1, (inp.clone(), out)))
+                    for (inp, out) in others {
+                        ui.label(format!("{} → {}", inp, out));
+                    }
+                    Self::draw_other_inps(&mut self.other_inputs, num_columns(&self.input), ui);
+                    if let Some(job) = &self.job {
+                        ui.horizontal(|ui| {
+                            ui.spinner();
+                            ui.label(format!("{} programs", job.progress.bank_entries()));
                         });
+                        if ui.button("Cancel").clicked() {
+                            job.cancel.store(true, Ordering::Relaxed);
+                        }
+                    } else if ui.button("Learn").clicked() {
+                        self.editable = false;
 
-                        let complete_examples = std::iter::once((0, (start.clone(), goal.clone())))
-                            .chain(complete_other_inps)
-                            .collect::<Vec<_>>(); 
-
-                        let mut ex_vsas = complete_examples.iter().map(|(i, (inp, out))| {
-                            let mut cache: HashMap<Lit, Rc<VSA<Lit, Fun>>> = HashMap::new();
-                            for (outs, vsa) in all_cache.iter() {
-                                if let Some(v) = cache.get_mut(&outs[*i]) {
-                                    *v = Rc::new(VSA::unify(vsa.clone(), v.clone()));
-                                } else {
-                                    cache.insert(outs[*i].clone(), vsa.clone());
-                                }
-                            }
-
-                            crate::synth::learn_to_depth(
-                                inp, 
-                                out, 
-                                &mut cache, 
-                                &bank,
-                                learn_depth,
+                        // the hole's own examples first, then the ones added in the editor
+                        let examples = std::iter::once((start.clone(), Some(goal.clone())))
+                            .chain(
+                                others
+                                    .iter()
+                                    .map(|(inp, out)| (inp.clone(), Some(out.clone()))),
                             )
-                        });
-
-                        let mut res = ex_vsas.next().unwrap();
+                            .chain(self.other_inputs.iter().cloned())
+                            .collect::<Vec<_>>();
 
-                        for vsa in ex_vsas {
-                            if let Some(prog) = res.pick_best(|ast| ast.cost()) {
-                                if complete_examples.iter().all(|(_, (inp, out))| prog.eval(inp) == *out) {
-                                    break;
-                                };
-                            }
+                        let inputs = examples
+                            .iter()
+                            .map(|(inp, _)| inp.clone())
+                            .collect::<Vec<_>>();
 
-                            res = Rc::new(res.intersect(vsa.as_ref()));
-                        }
+                        let complete_examples = examples
+                            .into_iter()
+                            .enumerate()
+                            .filter_map(|(i, (inp, out))| out.map(|out| (i, (inp, out))))
+                            .collect::<Vec<_>>();
 
-                        let new_vsa = Rc::into_inner(res);
-                        let self_mut = Rc::as_ptr(&self.vsa) as *mut _;
-                        // Safety: probably
-                        unsafe { std::ptr::write(self_mut, new_vsa) };
-                        let rich_vsa = RichVSA::new(
-                            self.vsa.clone(),
-                            self.input.clone(),
-                            self.goal.clone(),
-                            learn_pos.unwrap(),
-                            self.other_inputs.iter().map(|(inp, _)| (inp.clone(), None)).collect(),
-                            );
-                        self.children = rich_vsa.children;
-                        // TODO: send a signal and learn to depth
+                        let config = config.clone();
+                        self.job = Some(LearnJob::spawn(config.budget(), move |budget| {
+                            learn_examples(inputs, complete_examples, &config, learn_depth, budget)
+                        }));
                     }
                 });
             }
@@ -503,13 +590,13 @@ impl RichVSA {
             .find_map(|child| child.find_clicked_node(pos, egui_ctx))
     }
 
-    pub fn find_parent_of_vsa(&mut self, vsa: &Rc<VSA<Lit, Fun>>) -> Option<&mut RichVSA> {
-        if Rc::ptr_eq(&self.vsa, vsa) {
+    pub fn find_parent_of_vsa(&mut self, vsa: &Arc<VSA<Lit, Fun>>) -> Option<&mut RichVSA> {
+        if Arc::ptr_eq(&self.vsa, vsa) {
             return None;
         } else if self
             .children
                 .iter()
-                .any(|child| Rc::ptr_eq(&child.vsa, vsa))
+                .any(|child| Arc::ptr_eq(&child.vsa, vsa))
                 {
                     return Some(self);
                 }
@@ -519,15 +606,155 @@ impl RichVSA {
             .find_map(|child| child.find_parent_of_vsa(vsa))
     }
 
-    pub fn draw_other_inps(other_inps: &mut Vec<(Lit, Option<Lit>)>, ui: &mut egui::Ui) {
+    fn replace_vsa(&mut self, new_vsa: VSA<Lit, Fun>, pos: Vec2) {
+        let self_mut = Arc::as_ptr(&self.vsa) as *mut VSA<Lit, Fun>;
+        // Safety: probably
+        unsafe { std::ptr::write(self_mut, new_vsa) };
+        let rich_vsa = RichVSA::new(
+            self.vsa.clone(),
+            self.input.clone(),
+            self.goal.clone(),
+            pos,
+            self.other_inputs
+                .iter()
+                .map(|(inp, _)| (inp.clone(), None))
+                .collect(),
+        );
+        self.children = rich_vsa.children;
+        self.best = None;
+    }
+
+    // keep only the programs that agree with the picked answer, and remember
+    // it as an example
+    fn answer(&mut self, input: Lit, answer: Answer<StringDsl>, pos: Vec2) {
+        self.replace_vsa(answer.vsa.as_ref().clone(), pos);
+        match self.other_inputs.iter_mut().find(|(inp, _)| *inp == input) {
+            Some((_, out)) => *out = Some(answer.output),
+            None => self.other_inputs.push((input, Some(answer.output))),
+        }
+    }
+
+    pub fn draw_stopped(stopped: Option<Exhausted>, ui: &mut egui::Ui) {
+        if let Some(reason) = stopped {
+            ui.colored_label(egui::Color32::RED, format!("Stopped early: {reason}"));
+        }
+    }
+
+    // the cheapest programs, 10 at a time, with what they do on the other
+    // inputs to see which ones generalize
+    pub fn draw_best(
+        best: &mut Option<BestPrograms>,
+        vsa: &Arc<VSA<Lit, Fun>>,
+        other_inps: &[(Lit, Option<Lit>)],
+        ui: &mut egui::Ui,
+    ) {
+        let Some((ranked, shown)) = best else {
+            if ui.button("Best Programs").clicked() {
+                let mut ranked = VSA::ranked(vsa);
+                let shown = ranked.by_ref().take(10).collect();
+                *best = Some((ranked, shown));
+            }
+            return;
+        };
+
+        for (i, ast) in shown.iter().enumerate() {
+            ui.label(format!("{}. [cost {}] {}", i + 1, ast.cost(), ast));
+            for (inp, out) in other_inps {
+                let res = ast.eval(inp);
+                let text = format!("    {} → {}", inp, res);
+                match out {
+                    Some(out) if *out != res => ui.colored_label(egui::Color32::RED, text),
+                    _ => ui.label(text),
+                };
+            }
+        }
+        let mut hide = false;
+        ui.horizontal(|ui| {
+            if ui.button("Next 10").clicked() {
+                shown.extend(ranked.by_ref().take(10));
+            }
+            hide = ui.button("Hide").clicked();
+        });
+        if hide {
+            *best = None;
+        }
+    }
+
+    // an input the programs disagree on, picking one of the outputs narrows
+    // the node down to the programs that give it
+    pub fn draw_question(
+        question: &mut Option<Option<Question<StringDsl>>>,
+        vsa: &VSA<Lit, Fun>,
+        input: &Lit,
+        other_inps: &[(Lit, Option<Lit>)],
+        ui: &mut egui::Ui,
+    ) -> Option<(Lit, Answer<StringDsl>)> {
+        let Some(asked) = question else {
+            if ui.button("Suggest Input").clicked() {
+                let example_inputs = std::iter::once(input.clone())
+                    .chain(
+                        other_inps
+                            .iter()
+                            .filter(|(_, out)| out.is_some())
+                            .map(|(inp, _)| inp.clone()),
+                    )
+                    .collect::<Vec<_>>();
+                let unlabeled = other_inps
+                    .iter()
+                    .filter(|(_, out)| out.is_none())
+                    .map(|(inp, _)| inp.clone())
+                    .collect::<Vec<_>>();
+                *question = Some(distinguishing_input(vsa, &example_inputs, &unlabeled));
+            }
+            return None;
+        };
+
+        let mut picked = None;
+        match asked {
+            Some(asked) => {
+                ui.label(format!("What should {} give?", asked.input));
+                for answer in &asked.answers {
+                    ui.horizontal(|ui| {
+                        if ui.button("Pick").clicked() {
+                            picked = Some((asked.input.clone(), answer.clone()));
+                        }
+                        ui.label(format!("{}", answer.output));
+                        ui.label(format!("via {}", answer.program));
+                    });
+                }
+            }
+            None => {
+                ui.label("The programs agree on every input tried");
+            }
+        }
+        if picked.is_some() || ui.button("Dismiss").clicked() {
+            *question = None;
+        }
+        picked
+    }
+
+    // a text box for each column of an input
+    fn edit_input(inp: &mut Lit, ui: &mut egui::Ui) {
+        let cols = match inp {
+            Lit::Tuple(cols) => cols.iter_mut().collect(),
+            inp => vec![inp],
+        };
+        for col in cols {
+            if let Lit::StringConst(s) = col {
+                ui.text_edit_singleline(s);
+            }
+        }
+    }
+
+    pub fn draw_other_inps(
+        other_inps: &mut Vec<(Lit, Option<Lit>)>,
+        columns: usize,
+        ui: &mut egui::Ui,
+    ) {
         let mut kill_inps = std::collections::HashSet::new();
         for (other_inp, other_out) in other_inps.iter_mut() {
-            let mut other_inp_str = match other_inp {
-                Lit::StringConst(s) => s.clone(),
-                _ => todo!(),
-            };
             ui.horizontal(|ui| {
-                ui.text_edit_singleline(&mut other_inp_str);
+                Self::edit_input(other_inp, ui);
                 ui.label("→");
                 if let Some(Lit::StringConst(other_out_str)) = other_out {
                     ui.text_edit_singleline(other_out_str);
@@ -542,18 +769,15 @@ impl RichVSA {
                         kill_inps.insert(other_inp.clone());
                     }
                 }
-
-                *other_inp = Lit::StringConst(other_inp_str);
             });
         }
 
-        other_inps.retain(|(inp, _)| match inp {
-            Lit::StringConst(s) => !kill_inps.contains(&Lit::StringConst(s.clone())),
-            _ => todo!(),
-        });
+        other_inps.retain(|(inp, _)| !kill_inps.contains(inp));
 
         if ui.button("Add Example").clicked() {
-            other_inps.push((Lit::StringConst("".to_string()), None));
+            let mut inp = Lit::StringConst("".to_string());
+            set_columns(&mut inp, columns);
+            other_inps.push((inp, None));
         }
     }
 }
@@ -565,6 +789,27 @@ impl PartialEq for RichVSA {
     }
 }
 
+fn num_columns(inp: &Lit) -> usize {
+    match inp {
+        Lit::Tuple(cols) => cols.len(),
+        _ => 1,
+    }
+}
+
+// drops columns off the end or adds empty ones, a single column is a string
+fn set_columns(inp: &mut Lit, columns: usize) {
+    let mut cols = match std::mem::replace(inp, Lit::StringConst("".to_string())) {
+        Lit::Tuple(cols) => cols,
+        inp => vec![inp],
+    };
+    cols.resize(columns.max(1), Lit::StringConst("".to_string()));
+    *inp = if cols.len() == 1 {
+        cols.remove(0)
+    } else {
+        Lit::Tuple(cols)
+    };
+}
+
 fn draw_area_arrows(start_id: Id, end_id: Id, egui_ctx: &Context) {
     // TODO: use .rect()
     let positions = egui_ctx.memory(|mem| {
@@ -588,3 +833,72 @@ fn draw_arrow(id: Id, sp: egui::Pos2, ep: egui::Pos2, egui_ctx: &Context) {
     painter.line_segment([sp, ep], egui::Stroke::new(1.0, egui::Color32::BLACK));
     // painter.arrow(sp, vec, egui::Stroke::new(1.0, egui::Color32::WHITE));
 }
+
+// bottom up over every input, then learn each complete example and
+// intersect them
+fn learn_examples(
+    inputs: Vec<Lit>,
+    complete_examples: Vec<(usize, (Lit, Lit))>,
+    config: &SynthConfig<StringDsl>,
+    learn_depth: usize,
+    budget: &Budget,
+) -> VSA<Lit, Fun> {
+    let examples = complete_examples
+        .iter()
+        .map(|(_, ex)| ex.clone())
+        .collect::<Vec<_>>();
+
+    let (mut bank, mut regex_bank, mut all_cache) = config.banks(&examples, inputs.len());
+
+    for i in 1..=config.max_size {
+        let grown = interactive_vsa::synth::bottom_up::<StringDsl>(
+            inputs.iter(),
+            i,
+            &mut all_cache,
+            &mut bank,
+            &mut regex_bank,
+            &config.operators,
+            config.enable_bools(&examples),
+            budget,
+        );
+        if grown.is_err() {
+            break;
+        }
+    }
+    // dbg!(&bank);
+
+    let mut ex_vsas = complete_examples.iter().map(|(i, (inp, out))| {
+        let mut cache: HashMap<Lit, Arc<VSA<Lit, Fun>>> = HashMap::new();
+        for (outs, vsa) in all_cache.iter() {
+            if let Some(v) = cache.get_mut(&outs[*i]) {
+                *v = Arc::new(VSA::unify(vsa.clone(), v.clone()));
+            } else {
+                cache.insert(outs[*i].clone(), vsa.clone());
+            }
+        }
+
+        interactive_vsa::synth::learn_with::<StringDsl>(
+            inp,
+            out,
+            &cache,
+            &bank,
+            config,
+            learn_depth,
+            budget,
+        )
+    });
+
+    let mut res = ex_vsas.next().unwrap();
+
+    for vsa in ex_vsas {
+        if let Some((_, prog)) = res.pick_best() {
+            if examples.iter().all(|(inp, out)| prog.eval(inp) == *out) {
+                break;
+            };
+        }
+
+        res = Arc::new(res.intersect_within(vsa.as_ref(), budget));
+    }
+
+    Arc::unwrap_or_clone(res)
+}
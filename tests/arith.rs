@@ -0,0 +1,139 @@
+use std::collections::HashSet;
+
+use interactive_vsa::synth::witness::{ArithWitness, FormatIntWitness, ParseIntWitness};
+use interactive_vsa::{top_down, Bank, Fun, Language, Lit, Witness, WitnessCtx, AST, VSA};
+
+fn s(s: &str) -> Lit {
+    Lit::StringConst(s.to_string())
+}
+
+fn witness(witness: impl Witness<interactive_vsa::StringDsl>, inp: &Lit, goal: Lit) -> Vec<VSA> {
+    let bank = Bank::new();
+    let ctx = WitnessCtx { inp, bank: &bank };
+    witness.witness(&ctx, &goal)
+}
+
+// the operator and children goals of every Join
+fn joins(vsas: Vec<VSA>) -> HashSet<(Fun, Vec<Lit>)> {
+    vsas.into_iter()
+        .map(|vsa| match vsa {
+            VSA::Join {
+                op, children_goals, ..
+            } => (op, children_goals),
+            _ => panic!("expected a Join"),
+        })
+        .collect()
+}
+
+#[test]
+fn arithmetic_evaluates_and_fails_to_zero() {
+    let eval = |fun: Fun, a: i64, b: i64| fun.eval(&[Lit::IntConst(a), Lit::IntConst(b)], &s(""));
+    assert_eq!(eval(Fun::Add, 2, 3), Lit::IntConst(5));
+    assert_eq!(eval(Fun::Sub, 2, 3), Lit::IntConst(-1));
+    assert_eq!(eval(Fun::Mul, -4, 3), Lit::IntConst(-12));
+    assert_eq!(eval(Fun::Div, 7, 2), Lit::IntConst(3));
+    assert_eq!(eval(Fun::Div, 7, 0), Lit::IntConst(0));
+    assert_eq!(eval(Fun::Add, i64::MAX, 1), Lit::IntConst(0));
+}
+
+#[test]
+fn parses_and_formats_ints() {
+    let parse = |text: &str| Fun::ParseInt.eval(&[s(text)], &s(""));
+    assert_eq!(parse("42"), Lit::IntConst(42));
+    assert_eq!(parse(" -7 "), Lit::IntConst(-7));
+    assert_eq!(parse("forty"), Lit::IntConst(0));
+
+    let format =
+        |n: i64, width: i64| Fun::FormatInt.eval(&[Lit::IntConst(n), Lit::IntConst(width)], &s(""));
+    assert_eq!(format(7, 0), s("7"));
+    assert_eq!(format(7, 3), s("007"));
+    assert_eq!(format(1234, 2), s("1234"));
+}
+
+#[test]
+fn parse_int_witness_finds_each_spelling() {
+    let found = joins(witness(
+        ParseIntWitness,
+        &s("a 007 b 7 c 7"),
+        Lit::IntConst(7),
+    ));
+    assert_eq!(
+        found,
+        HashSet::from([
+            (Fun::ParseInt, vec![s("007")]),
+            (Fun::ParseInt, vec![s("7")])
+        ])
+    );
+    assert!(witness(ParseIntWitness, &s("a 8"), Lit::IntConst(7)).is_empty());
+}
+
+#[test]
+fn format_int_witness_keeps_the_padding() {
+    let found = witness(FormatIntWitness, &s(""), s("007"));
+    let [VSA::Join { op, children, .. }] = found.as_slice() else {
+        panic!("expected one Join");
+    };
+    assert_eq!(*op, Fun::FormatInt);
+    let widths = VSA::ranked(&children[1]).collect::<HashSet<_>>();
+    assert_eq!(widths, HashSet::from([AST::Lit(Lit::IntConst(3))]));
+    assert!(witness(FormatIntWitness, &s(""), s("7a")).is_empty());
+}
+
+#[test]
+fn arith_witness_inverts_every_operator() {
+    let int = Lit::IntConst;
+    let found = joins(witness(ArithWitness, &s("x 6 y"), int(3)));
+    assert_eq!(
+        found,
+        HashSet::from([
+            (Fun::Add, vec![int(6), int(-3)]),
+            (Fun::Sub, vec![int(6), int(3)]),
+            (Fun::Div, vec![int(6), int(2)]),
+        ])
+    );
+
+    // division rounds down, so 7 / 3 is the only way to 2
+    let found = joins(witness(ArithWitness, &s("7"), int(2)));
+    assert!(found.contains(&(Fun::Div, vec![int(7), int(3)])));
+
+    // no +0 or *1
+    let found = joins(witness(ArithWitness, &s("6"), int(6)));
+    assert!(found.is_empty(), "{found:?}");
+}
+
+#[test]
+fn arith_witness_skips_overflows() {
+    let int = Lit::IntConst;
+    // i64::MIN % -1 and i64::MIN / -1 overflow, so only + and - are left
+    let found = joins(witness(ArithWitness, &s("x -1"), int(i64::MIN)));
+    assert_eq!(
+        found,
+        HashSet::from([
+            (Fun::Add, vec![int(-1), int(i64::MIN + 1)]),
+            (Fun::Sub, vec![int(-1), int(i64::MAX)]),
+        ])
+    );
+    let found = joins(witness(ArithWitness, &s("x -9223372036854775808"), int(-1)));
+    assert!(!found.iter().any(|(op, _)| *op == Fun::Div));
+
+    let examples = [(s("x -1"), s("-9223372036854775808"))];
+    let (_, ast) = top_down(&examples);
+    if let Some(ast) = ast {
+        assert_eq!(ast.eval(&examples[0].0), examples[0].1, "{ast}");
+    }
+}
+
+#[test]
+fn learns_to_add_one() {
+    let examples = [
+        (s("I have 17 cookies"), s("18")),
+        (s("Give me 3"), s("4")),
+        (s("5 apples"), s("6")),
+    ];
+    let (_, ast) = top_down(&examples);
+    let ast = ast.expect("no program found");
+    for (inp, out) in &examples {
+        assert_eq!(ast.eval(inp), *out, "{ast} on {inp}");
+    }
+    assert_eq!(ast.eval(&s("only 41 left")), s("42"), "{ast}");
+}
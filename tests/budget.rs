@@ -0,0 +1,53 @@
+use std::time::Duration;
+
+use interactive_vsa::{top_down_within, Budget, Exhausted, Lit, StringDsl, SynthConfig};
+
+fn s(s: &str) -> Lit {
+    Lit::StringConst(s.to_string())
+}
+
+// whatever the budget cuts off, a program that's returned fits every example
+#[test]
+fn exhausted_budget_never_returns_a_wrong_program() {
+    let examples = [
+        (s("Ada Lovelace"), s("Ada")),
+        (s("Alan Turing"), s("Alan")),
+        (s("Grace Hopper"), s("Grace")),
+    ];
+    let config: SynthConfig<StringDsl> = SynthConfig::default();
+    for max_vsa_nodes in (0..2000).step_by(25) {
+        let budget = Budget::unlimited().with_max_vsa_nodes(max_vsa_nodes);
+        let (_, ast, exhausted) = top_down_within(&examples, &config, &budget);
+        if let Some(ast) = ast {
+            for (inp, out) in &examples {
+                assert_eq!(
+                    ast.eval(inp),
+                    *out,
+                    "{ast} on {inp} with {max_vsa_nodes} nodes, {exhausted:?}"
+                );
+            }
+        }
+    }
+}
+
+// intersecting the VSAs of these two examples used to take gigabytes before
+// the budget was checked again
+#[test]
+fn dates_stop_with_the_reason() {
+    let examples = [
+        (s("2023-01-05"), s("05/01/2023")),
+        (s("1999-12-31"), s("31/12/1999")),
+    ];
+    let config: SynthConfig<StringDsl> = SynthConfig::default();
+    for max_vsa_nodes in [1000, 10_000, 20_000] {
+        let budget = Budget::unlimited().with_max_vsa_nodes(max_vsa_nodes);
+        let (vsa, ast, exhausted) = top_down_within(&examples, &config, &budget);
+        assert_eq!(exhausted, Some(Exhausted::VSANodes));
+        assert_eq!(ast, None);
+        assert!(vsa.stats().nodes <= max_vsa_nodes, "{}", vsa.stats());
+    }
+
+    let budget = Budget::unlimited().with_timeout(Duration::from_millis(1));
+    let (_, _, exhausted) = top_down_within(&examples, &config, &budget);
+    assert_eq!(exhausted, Some(Exhausted::Deadline));
+}
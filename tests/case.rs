@@ -0,0 +1,74 @@
+use std::collections::HashSet;
+
+use interactive_vsa::synth::witness::CaseWitness;
+use interactive_vsa::{
+    bottom_up, top_down, Bank, Fun, Lit, StringDsl, SynthConfig, Witness, WitnessCtx, VSA,
+};
+
+fn s(s: &str) -> Lit {
+    Lit::StringConst(s.to_string())
+}
+
+// the operator and children goals of every Join the case witness returns
+fn case_joins(inp: &Lit, goal: Lit) -> HashSet<(Fun, Vec<Lit>)> {
+    let bank = Bank::new();
+    let ctx = WitnessCtx { inp, bank: &bank };
+    CaseWitness
+        .witness(&ctx, &goal)
+        .into_iter()
+        .map(|vsa| match vsa {
+            VSA::Join {
+                op, children_goals, ..
+            } => (op, children_goals),
+            _ => panic!("expected a Join"),
+        })
+        .collect()
+}
+
+#[test]
+fn case_witness_finds_the_substrings() {
+    let inp = s("john smith, John");
+    assert_eq!(
+        case_joins(&inp, s("JOHN")),
+        HashSet::from([
+            (Fun::Uppercase, vec![s("john")]),
+            (Fun::Uppercase, vec![s("John")]),
+        ])
+    );
+    assert_eq!(
+        case_joins(&inp, s("smith")),
+        HashSet::from([(Fun::Lowercase, vec![s("smith")])])
+    );
+    // nothing to convert without cased letters, or nowhere to find it
+    assert!(case_joins(&inp, s(", ")).is_empty());
+    assert!(case_joins(&inp, s("ADA")).is_empty());
+}
+
+#[test]
+fn bottom_up_converts_case() {
+    let config = SynthConfig::<StringDsl>::default();
+    let examples = [(s("John Smith"), s(""))];
+    let (mut bank, mut token_bank, mut cache) = config.banks(&examples, 1);
+    for size in 1..=2 {
+        bottom_up::<StringDsl>(
+            examples.iter().map(|(inp, _)| inp),
+            size,
+            &mut cache,
+            &mut bank,
+            &mut token_bank,
+            &config.operators,
+            false,
+            &config.budget(),
+        )
+        .unwrap();
+    }
+    assert!(cache.contains_key(&vec![s("JOHN SMITH")]));
+    assert!(cache.contains_key(&vec![s("john smith")]));
+}
+
+#[test]
+fn learns_an_uppercased_first_name() {
+    let (_, ast) = top_down(&[(s("john smith"), s("JOHN")), (s("ada lovelace"), s("ADA"))]);
+    let ast = ast.expect("no program found");
+    assert_eq!(ast.eval(&s("grace hopper")), s("GRACE"), "{ast}");
+}
@@ -0,0 +1,83 @@
+use std::sync::Arc;
+
+use interactive_vsa::{top_down, Fun, Lit, AST, VSA};
+
+fn s(s: &str) -> Lit {
+    Lit::StringConst(s.to_string())
+}
+
+fn leaf(asts: impl IntoIterator<Item = AST>) -> Arc<VSA> {
+    Arc::new(VSA::Leaf(asts.into_iter().map(Arc::new).collect()))
+}
+
+fn x() -> AST {
+    AST::Lit(Lit::Input(0))
+}
+
+// (X | 'a') <> ('b' | X)
+fn concat_join() -> VSA {
+    VSA::Join {
+        op: Fun::Concat,
+        children: vec![leaf([x(), AST::Lit(s("a"))]), leaf([AST::Lit(s("b")), x()])],
+        children_goals: vec![s("a"), s("b")],
+    }
+}
+
+// every cluster only has programs with the cluster's output
+fn check_outputs(vsa: &VSA, input: &Lit) -> Vec<(Lit, u128)> {
+    let mut sizes = Vec::new();
+    for (out, cluster) in vsa.cluster(input) {
+        let out = out.expect("every program is learned");
+        let cluster = Arc::new(cluster.as_ref().clone());
+        for ast in VSA::ranked(&cluster) {
+            assert_eq!(ast.eval(input), out, "{ast} on {input}");
+        }
+        sizes.push((out, cluster.count_programs()));
+    }
+    sizes.sort_by_key(|(out, _)| out.to_string());
+    sizes
+}
+
+#[test]
+fn join_clusters_are_products_of_the_childrens() {
+    let join = concat_join();
+    assert_eq!(
+        check_outputs(&join, &s("a")),
+        vec![(s("aa"), 2), (s("ab"), 2)]
+    );
+    assert_eq!(
+        check_outputs(&join, &s("z")),
+        vec![(s("ab"), 1), (s("az"), 1), (s("zb"), 1), (s("zz"), 1)]
+    );
+}
+
+#[test]
+fn union_clusters_merge_equal_outputs() {
+    let union = VSA::Union(vec![Arc::new(concat_join()), leaf([AST::Lit(s("aa"))])]);
+    assert_eq!(
+        check_outputs(&union, &s("a")),
+        vec![(s("aa"), 3), (s("ab"), 2)]
+    );
+}
+
+#[test]
+fn unlearned_nodes_only_know_their_examples() {
+    let hole = VSA::Unlearned {
+        start: s("a"),
+        goal: s("A"),
+        others: vec![(s("b"), s("B"))],
+    };
+    let on = |input: &str| hole.cluster(&s(input)).into_keys().collect::<Vec<_>>();
+    assert_eq!(on("b"), vec![Some(s("B"))]);
+    assert_eq!(on("c"), vec![None]);
+}
+
+#[test]
+fn clusters_of_a_learned_vsa_cover_it() {
+    let (vsa, _) = top_down(&[(s("Ada Lovelace"), s("Ada"))]);
+    let total = check_outputs(&vsa, &s("Alan Turing"))
+        .into_iter()
+        .map(|(_, n)| n)
+        .sum::<u128>();
+    assert_eq!(total, vsa.count_programs());
+}
@@ -0,0 +1,114 @@
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+use interactive_vsa::synth::witness::ColumnWitness;
+use interactive_vsa::{top_down, Bank, Fun, Lit, Task, Witness, WitnessCtx, AST, VSA};
+
+fn s(s: &str) -> Lit {
+    Lit::StringConst(s.to_string())
+}
+
+fn cols(cols: &[&str]) -> Lit {
+    Lit::Tuple(cols.iter().map(|col| s(col)).collect())
+}
+
+#[test]
+fn inputs_pick_their_column() {
+    let inp = cols(&["Ada", "Lovelace"]);
+    assert_eq!(AST::Lit(Lit::Input(0)).eval(&inp), s("Ada"));
+    assert_eq!(AST::Lit(Lit::Input(1)).eval(&inp), s("Lovelace"));
+
+    // (λX.X[0..1])(X1), the body only sees the column it's applied to
+    let initial = AST::App {
+        fun: Fun::Apply,
+        args: vec![
+            AST::Lit(Lit::Input(1)),
+            AST::App {
+                fun: Fun::Slice,
+                args: vec![AST::Lit(Lit::LocConst(0)), AST::Lit(Lit::LocConst(1))],
+            },
+        ],
+    };
+    assert_eq!(initial.eval(&inp), s("L"));
+}
+
+#[test]
+fn column_witness_applies_to_the_columns_with_the_goal() {
+    let inp = cols(&["John", "Smith"]);
+    let bank = Bank::new();
+    let ctx = WitnessCtx {
+        inp: &inp,
+        bank: &bank,
+    };
+    let goals = |goal: Lit| {
+        ColumnWitness
+            .witness(&ctx, &goal)
+            .into_iter()
+            .map(|vsa| match vsa {
+                VSA::Join {
+                    op: Fun::Apply,
+                    children_goals,
+                    ..
+                } => children_goals,
+                _ => panic!("expected an Apply"),
+            })
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(goals(s("jo")), [vec![s("John"), s("jo")]]);
+    assert_eq!(
+        goals(s("H")),
+        [vec![s("John"), s("H")], vec![s("Smith"), s("H")]]
+    );
+    // a whole column is already in the bank
+    assert!(goals(s("Smith")).is_empty());
+    assert!(goals(s("Ada")).is_empty());
+}
+
+#[test]
+fn learns_from_a_csv_with_two_columns() {
+    let task = Task::from_csv(
+        "first,last,output\n\
+         John,Smith,J. Smith\n\
+         Ada,Lovelace,A. Lovelace\n\
+         Grace,Hopper,\n",
+    )
+    .unwrap();
+    assert_eq!(task.columns, ["first", "last"]);
+    assert_eq!(task.num_columns(), 2);
+    assert_eq!(task.examples[0], (cols(&["John", "Smith"]), s("J. Smith")));
+    assert_eq!(task.other_inputs, [cols(&["Grace", "Hopper"])]);
+
+    let (_, ast) = top_down(&task.examples);
+    let ast = ast.expect("no program found");
+    assert_eq!(ast.eval(&task.other_inputs[0]), s("G. Hopper"), "{ast}");
+    assert_eq!(
+        ast.eval(&cols(&["Alan", "Turing"])),
+        s("A. Turing"),
+        "{ast}"
+    );
+}
+
+// the columns of a line are separated by tabs like the output
+#[test]
+fn cli_reads_tab_separated_columns() {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_vsa-synth"))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(b"John\tSmith\tJ. Smith\nAda\tLovelace\tA. Lovelace\n")
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success());
+
+    // the first column's initial and the whole second column
+    let program = String::from_utf8(output.stdout).unwrap();
+    assert!(program.contains("(λX.(X[0..1]))(X)"), "{program}");
+    assert!(program.contains("X1"), "{program}");
+}
@@ -0,0 +1,50 @@
+use interactive_vsa::{top_down, Fun, Language, Lit, AST};
+
+fn s(s: &str) -> Lit {
+    Lit::StringConst(s.to_string())
+}
+
+// the lambda's outputs on each part are joined, the separator isn't kept
+#[test]
+fn concat_map_joins_the_outputs() {
+    let inp = s("Ada King Lovelace");
+    assert_eq!(
+        Fun::ConcatMap.lambda_inputs(&[s(" ")], &inp),
+        [s("Ada"), s("King"), s("Lovelace")]
+    );
+    assert_eq!(
+        Fun::ConcatMap.eval(&[s(" "), s("A."), s("K."), s("L.")], &inp),
+        s("A.K.L.")
+    );
+
+    let initial = AST::App {
+        fun: Fun::Slice,
+        args: vec![AST::Lit(Lit::LocConst(0)), AST::Lit(Lit::LocConst(1))],
+    };
+    let prog = AST::App {
+        fun: Fun::ConcatMap,
+        args: vec![AST::Lit(s(" ")), initial],
+    };
+    assert_eq!(prog.eval(&inp), s("AKL"));
+}
+
+#[test]
+fn learns_initials_of_every_word() {
+    let (_, ast) = top_down(&[
+        (s("John Ronald Tolkien"), s("J.R.T.")),
+        (s("Grace Brewster Hopper"), s("G.B.H.")),
+    ]);
+    let ast = ast.expect("no program found");
+    assert!(
+        matches!(
+            ast,
+            AST::App {
+                fun: Fun::ConcatMap,
+                ..
+            }
+        ),
+        "expected a concat_map, got {ast}"
+    );
+    assert_eq!(ast.eval(&s("Alan Mathison Turing")), s("A.M.T."), "{ast}");
+    assert_eq!(ast.eval(&s("Ada Lovelace")), s("A.L."), "{ast}");
+}
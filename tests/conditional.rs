@@ -0,0 +1,63 @@
+use interactive_vsa::{top_down_with, Fun, Lit, StringDsl, SynthConfig, AST};
+
+fn s(s: &str) -> Lit {
+    Lit::StringConst(s.to_string())
+}
+
+// a conditional is only picked once no single program fits up to the max
+// size, so it's kept small to not enumerate the biggest banks
+fn config() -> SynthConfig<StringDsl> {
+    SynthConfig {
+        max_size: 4,
+        ..SynthConfig::default()
+    }
+}
+
+fn learn_if(examples: &[(Lit, Lit)]) -> AST {
+    let (_, ast) = top_down_with(examples, &config());
+    let ast = ast.expect("no program found");
+    assert!(
+        matches!(ast, AST::App { fun: Fun::If, .. }),
+        "expected a conditional, got {ast}"
+    );
+    for (inp, out) in examples {
+        assert_eq!(ast.eval(inp), *out, "{ast} on {inp}");
+    }
+    ast
+}
+
+#[test]
+fn branches_on_punctuation() {
+    let ast = learn_if(&[
+        (s("Hopper, Grace"), s("Grace")),
+        (s("Smith, John"), s("John")),
+        (s("Ada Lovelace"), s("Ada")),
+        (s("Alan Turing"), s("Alan")),
+    ]);
+    assert_eq!(ast.eval(&s("Turing, Alan")), s("Alan"), "{ast}");
+    assert_eq!(ast.eval(&s("Grace Hopper")), s("Grace"), "{ast}");
+}
+
+#[test]
+fn one_example_per_branch() {
+    learn_if(&[(s("Smith, John"), s("John")), (s("Ada Lovelace"), s("Ada"))]);
+}
+
+// With positions up to 5 as primitives, `X[1..$] if 3 == $ else X[4..$]` is
+// there from size 1 on, but finding the digits takes a bigger bank
+#[test]
+fn a_single_program_beats_a_smaller_conditional() {
+    let mut config = config();
+    config.primitives = [Lit::Input(0), Lit::LocEnd]
+        .into_iter()
+        .chain((0..=5).map(Lit::LocConst))
+        .collect();
+    let examples = [(s("a12"), s("12")), (s("bcde3"), s("3"))];
+    let (_, ast) = top_down_with(&examples, &config);
+    let ast = ast.expect("no program found");
+    assert!(
+        !matches!(ast, AST::App { fun: Fun::If, .. }),
+        "expected a single program, got {ast}"
+    );
+    assert_eq!(ast.eval(&s("xy789")), s("789"), "{ast}");
+}
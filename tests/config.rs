@@ -0,0 +1,80 @@
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+
+use interactive_vsa::{
+    top_down_with, Fun, Lit, StringDsl, SynthConfig, Witness, WitnessCtx, AST, VSA,
+};
+
+fn s(s: &str) -> Lit {
+    Lit::StringConst(s.to_string())
+}
+
+fn uses(ast: &AST, op: Fun) -> bool {
+    match ast {
+        AST::App { fun, args } => *fun == op || args.iter().any(|arg| uses(arg, op)),
+        AST::Lit(_) => false,
+        AST::JS { input, .. } => uses(input, op),
+    }
+}
+
+// the witnesses and the bottom up fallback only build the config's operators
+#[test]
+fn disabled_operators_are_never_learned() {
+    let examples = [(s("Ada Lovelace"), s("Ada")), (s("Alan Turing"), s("Alan"))];
+
+    let (_, ast) = top_down_with(&examples, &SynthConfig::<StringDsl>::default());
+    let ast = ast.expect("no program found");
+    assert!(uses(&ast, Fun::Slice), "{ast}");
+
+    let mut config = SynthConfig::<StringDsl>::default();
+    config.operators.retain(|op| *op != Fun::Slice);
+    config.max_size = 3;
+    let (vsa, ast) = top_down_with(&examples, &config);
+    if let Some(ast) = ast {
+        assert!(!uses(&ast, Fun::Slice), "{ast}");
+    }
+    for ast in VSA::ranked(&Arc::new(vsa)).take(1000) {
+        assert!(!uses(&ast, Fun::Slice), "{ast}");
+    }
+}
+
+// counts the goals it's asked for without building anything
+struct Counting(Arc<AtomicUsize>);
+
+impl Witness<StringDsl> for Counting {
+    fn name(&self) -> &'static str {
+        "counting"
+    }
+
+    fn witness(&self, _ctx: &WitnessCtx<StringDsl>, _goal: &Lit) -> Vec<VSA> {
+        self.0.fetch_add(1, Ordering::Relaxed);
+        Vec::new()
+    }
+}
+
+#[test]
+fn top_down_uses_the_configs_witnesses() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let mut config = SynthConfig::<StringDsl>::default();
+    config.witnesses.insert(0, Counting(calls.clone()));
+    let (_, ast) = top_down_with(&[(s("Ada Lovelace"), s("Ada"))], &config);
+    assert!(ast.is_some());
+    assert!(calls.load(Ordering::Relaxed) > 0);
+
+    calls.store(0, Ordering::Relaxed);
+    config.witnesses.set_enabled("counting", false);
+    top_down_with(&[(s("Ada Lovelace"), s("Ada"))], &config);
+    assert_eq!(calls.load(Ordering::Relaxed), 0);
+}
+
+#[test]
+fn json_witnesses_are_the_only_ones_tried() {
+    let config = SynthConfig::from_json(r#"{ "witnesses": ["slice", "const"] }"#).unwrap();
+    let names = config.witnesses.names().collect::<Vec<_>>();
+    assert_eq!(names[..2], [("slice", true), ("const", true)]);
+    assert!(names[2..].iter().all(|(_, enabled)| !enabled), "{names:?}");
+
+    assert!(SynthConfig::from_json(r#"{ "witnesses": ["reverse"] }"#).is_err());
+}
@@ -0,0 +1,68 @@
+use std::sync::Arc;
+
+use interactive_vsa::{distinguishing_input, Dsl, Fun, Lit, StringDsl, AST, VSA};
+
+fn s(s: &str) -> Lit {
+    Lit::StringConst(s.to_string())
+}
+
+// X[0..3] and "Ada", which agree on "Ada Lovelace"
+fn first_three_or_ada() -> VSA {
+    let slice = AST::App {
+        fun: Fun::Slice,
+        args: vec![AST::Lit(Lit::LocConst(0)), AST::Lit(Lit::LocConst(3))],
+    };
+    VSA::Leaf(
+        [slice, AST::Lit(s("Ada"))]
+            .into_iter()
+            .map(Arc::new)
+            .collect(),
+    )
+}
+
+#[test]
+fn asks_about_an_unlabeled_input() {
+    let examples = [s("Ada Lovelace")];
+    let question = distinguishing_input::<StringDsl>(
+        &first_three_or_ada(),
+        &examples,
+        &[s("Ada Lovelace"), s("Alan Turing")],
+    )
+    .expect("no question");
+
+    assert_eq!(question.input, s("Alan Turing"));
+    let answers = question
+        .answers
+        .iter()
+        .map(|answer| (answer.output.clone(), answer.cost))
+        .collect::<Vec<_>>();
+    // the constant is cheaper than the slice
+    assert_eq!(answers, [(s("Ada"), 2), (s("Ala"), 3)]);
+    for answer in &question.answers {
+        assert_eq!(answer.program.eval(&question.input), answer.output);
+        assert_eq!(answer.vsa.count_programs(), 1);
+    }
+}
+
+#[test]
+fn makes_up_an_input_without_unlabeled_ones() {
+    let examples = [s("Ada Lovelace")];
+    let question = distinguishing_input::<StringDsl>(&first_three_or_ada(), &examples, &[])
+        .expect("no question");
+
+    assert!(
+        StringDsl::mutate_input(&examples[0]).contains(&question.input),
+        "{}",
+        question.input
+    );
+    assert_eq!(question.answers.len(), 2);
+    assert!(question.answers[0].cost <= question.answers[1].cost);
+}
+
+#[test]
+fn no_question_when_the_programs_agree() {
+    let vsa = VSA::Leaf([Arc::new(AST::Lit(s("Ada")))].into_iter().collect());
+    let question =
+        distinguishing_input::<StringDsl>(&vsa, &[s("Ada Lovelace")], &[s("Alan Turing")]);
+    assert!(question.is_none());
+}
@@ -0,0 +1,88 @@
+use std::{sync::Arc, time::Duration};
+
+use interactive_vsa::{top_down_within, Budget, Fun, Lit, StringDsl, SynthConfig, AST, VSA};
+
+fn s(s: &str) -> Lit {
+    Lit::StringConst(s.to_string())
+}
+
+fn leaf(asts: impl IntoIterator<Item = AST>) -> Arc<VSA> {
+    Arc::new(VSA::Leaf(asts.into_iter().map(Arc::new).collect()))
+}
+
+// X <> c for each of the constants c
+fn concat_join(consts: &[String]) -> VSA {
+    VSA::Join {
+        op: Fun::Concat,
+        children: vec![
+            leaf([AST::Lit(Lit::Input(0))]),
+            leaf(consts.iter().map(|c| AST::Lit(s(c)))),
+        ],
+        children_goals: vec![s("a"), s("0")],
+    }
+}
+
+fn hole() -> VSA {
+    VSA::Unlearned {
+        start: s("a"),
+        goal: s("a7"),
+        others: vec![(s("b"), s("b7"))],
+    }
+}
+
+#[test]
+fn small_joins_are_checked_exactly() {
+    let consts = (0..10).map(|n| n.to_string()).collect::<Vec<_>>();
+    let (vsa, approximate) = hole().intersect_checked(&concat_join(&consts), &Budget::unlimited());
+    assert!(!approximate);
+    let programs = VSA::ranked(&Arc::new(vsa)).collect::<Vec<_>>();
+    assert_eq!(programs.len(), 1);
+    assert_eq!(programs[0].eval(&s("b")), s("b7"));
+}
+
+#[test]
+fn large_joins_are_marked_approximate() {
+    let consts = (0..5000).map(|n| n.to_string()).collect::<Vec<_>>();
+    let (vsa, approximate) = hole().intersect_checked(&concat_join(&consts), &Budget::unlimited());
+    assert!(approximate);
+    assert_eq!(vsa.count_programs(), 5000);
+}
+
+#[test]
+fn unlearned_joins_are_marked_approximate() {
+    let join = VSA::Join {
+        op: Fun::Concat,
+        children: vec![leaf([AST::Lit(Lit::Input(0))]), Arc::new(hole())],
+        children_goals: vec![s("a"), s("7")],
+    };
+    let (_, approximate) = hole().intersect_checked(&join, &Budget::unlimited());
+    assert!(approximate);
+}
+
+#[test]
+fn exhausted_budget_is_marked_approximate() {
+    let consts = (0..10).map(|n| n.to_string()).collect::<Vec<_>>();
+    let budget = Budget::unlimited().with_max_vsa_nodes(0);
+    let (_, approximate) = hole().intersect_checked(&concat_join(&consts), &budget);
+    assert!(approximate);
+}
+
+// The VSAs of the two examples are DAGs that share most of their nodes.
+// Intersected pair of nodes by pair of nodes, without memoizing, they'd be
+// unfolded into millions of nodes.
+#[test]
+fn shared_nodes_are_intersected_once() {
+    let examples = [
+        (s("2023-01-05"), s("05/01/2023")),
+        (s("1999-12-31"), s("31/12/1999")),
+    ];
+    let config: SynthConfig<StringDsl> = SynthConfig::default();
+    let budget = Budget::unlimited()
+        .with_timeout(Duration::from_secs(5))
+        .with_max_vsa_nodes(100_000);
+    let (vsa, ast, exhausted) = top_down_within(&examples, &config, &budget);
+    assert_eq!(exhausted, None);
+    let ast = ast.expect("no program found");
+    assert_eq!(ast.eval(&s("2000-02-29")), s("29/02/2000"), "{ast}");
+    assert!(vsa.stats().nodes < 1000, "{}", vsa.stats());
+}
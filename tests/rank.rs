@@ -0,0 +1,29 @@
+use interactive_vsa::{Lit, Ranking, StringDsl, SynthConfig, Training};
+
+fn s(s: &str) -> Lit {
+    Lit::StringConst(s.to_string())
+}
+
+#[test]
+fn counts_the_tasks_it_trains_on() {
+    let config: SynthConfig<StringDsl> = SynthConfig::default();
+    let training = Training {
+        epochs: 3,
+        ..Training::default()
+    };
+    let ambiguous = vec![(s("Ada Lovelace"), s("Ada")), (s("Alan Turing"), s("Alan"))];
+    // one example, nothing to tell right from wrong
+    let single = vec![(s("Grace Hopper"), s("Grace"))];
+
+    let mut ranking = Ranking::default();
+    let (trained, solved) = ranking.train::<StringDsl>(&[ambiguous, single], &config, training);
+    assert_eq!(trained, 1);
+    assert_eq!(solved.len(), 3);
+    assert!(solved.iter().all(|&n| n <= trained));
+
+    let mut ranking = Ranking::default();
+    let single = vec![(s("Grace Hopper"), s("Grace"))];
+    let (trained, solved) = ranking.train::<StringDsl>(&[single], &config, training);
+    assert_eq!(trained, 0);
+    assert_eq!(solved, vec![0; 3]);
+}
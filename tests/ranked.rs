@@ -0,0 +1,94 @@
+use std::{collections::HashSet, sync::Arc};
+
+use interactive_vsa::{top_down, Cost, Fun, Lit, AST, VSA};
+
+fn s(s: &str) -> Lit {
+    Lit::StringConst(s.to_string())
+}
+
+fn lit(l: Lit) -> AST {
+    AST::Lit(l)
+}
+
+fn concat(lhs: AST, rhs: AST) -> AST {
+    AST::App {
+        fun: Fun::Concat,
+        args: vec![lhs, rhs],
+    }
+}
+
+fn union(asts: impl IntoIterator<Item = AST>) -> Arc<VSA> {
+    Arc::new(VSA::Union(
+        asts.into_iter()
+            .map(|ast| Arc::new(VSA::singleton(ast)))
+            .collect(),
+    ))
+}
+
+// X | ('ab' | X) <> ('c' | (X <> 'c'))
+fn example_vsa() -> Arc<VSA> {
+    let x = lit(Lit::Input(0));
+    let join = VSA::Join {
+        op: Fun::Concat,
+        children: vec![
+            union([x.clone(), lit(s("ab"))]),
+            union([lit(s("c")), concat(x.clone(), lit(s("c")))]),
+        ],
+        children_goals: vec![s("ab"), s("c")],
+    };
+    Arc::new(VSA::Union(vec![
+        Arc::new(VSA::singleton(x)),
+        Arc::new(join),
+    ]))
+}
+
+#[test]
+fn enumerates_every_program_cheapest_first() {
+    let vsa = example_vsa();
+    let ranked = VSA::ranked(&vsa).collect::<Vec<_>>();
+
+    let costs = ranked.iter().map(AST::cost).collect::<Vec<_>>();
+    assert_eq!(costs, vec![1, 4, 4, 6, 6]);
+    assert_eq!(ranked[0], lit(Lit::Input(0)));
+
+    let x = || lit(Lit::Input(0));
+    let expected = HashSet::from([
+        x(),
+        concat(x(), lit(s("c"))),
+        concat(lit(s("ab")), lit(s("c"))),
+        concat(x(), concat(x(), lit(s("c")))),
+        concat(lit(s("ab")), concat(x(), lit(s("c")))),
+    ]);
+    assert_eq!(ranked.into_iter().collect::<HashSet<_>>(), expected);
+}
+
+#[test]
+fn first_ranked_is_the_best_program() {
+    let vsa = example_vsa();
+    let (cost, best) = vsa.pick_best().unwrap();
+    assert_eq!(VSA::ranked(&vsa).next(), Some(best.clone()));
+    assert_eq!(cost, best.cost());
+}
+
+#[test]
+fn ranks_a_learned_vsa() {
+    let examples = [(s("Ada Lovelace"), s("Ada"))];
+    let (vsa, _) = top_down(&examples);
+    let vsa = Arc::new(vsa);
+
+    let first = VSA::ranked(&vsa).take(50).collect::<Vec<_>>();
+    assert_eq!(first.len() as u128, vsa.count_programs().min(50));
+    assert_eq!(first[0], vsa.pick_best().unwrap().1);
+    for pair in first.windows(2) {
+        assert!(
+            pair[0].cost() <= pair[1].cost(),
+            "{} before {}",
+            pair[0],
+            pair[1]
+        );
+    }
+    assert_eq!(first.iter().collect::<HashSet<_>>().len(), first.len());
+    for ast in &first {
+        assert_eq!(ast.eval(&examples[0].0), examples[0].1, "{ast}");
+    }
+}
@@ -0,0 +1,91 @@
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
+
+use interactive_vsa::{Fun, Lit, Weighting, AST, VSA};
+
+fn s(s: &str) -> Lit {
+    Lit::StringConst(s.to_string())
+}
+
+fn lit(l: Lit) -> AST {
+    AST::Lit(l)
+}
+
+fn leaf(asts: impl IntoIterator<Item = AST>) -> Arc<VSA> {
+    Arc::new(VSA::Leaf(asts.into_iter().map(Arc::new).collect()))
+}
+
+// X | ('a' | 'b' | 'c') <> ('d' | 'e'), built fresh so that the leaves' sets
+// can iterate in a different order every time
+fn example_vsa() -> Arc<VSA> {
+    let join = VSA::Join {
+        op: Fun::Concat,
+        children: vec![
+            leaf([lit(s("a")), lit(s("b")), lit(s("c"))]),
+            leaf([lit(s("d")), lit(s("e"))]),
+        ],
+        children_goals: vec![s("a"), s("d")],
+    };
+    Arc::new(VSA::Union(vec![leaf([lit(Lit::Input(0))]), Arc::new(join)]))
+}
+
+fn samples(weighting: Weighting, seed: u64, n: usize) -> Vec<AST> {
+    VSA::sample(&example_vsa(), weighting, seed)
+        .take(n)
+        .collect()
+}
+
+#[test]
+fn same_seed_same_programs() {
+    for weighting in [Weighting::Uniform, Weighting::Cost { decay: 0.5 }] {
+        for seed in 0..5 {
+            assert_eq!(samples(weighting, seed, 50), samples(weighting, seed, 50));
+        }
+    }
+}
+
+#[test]
+fn different_seeds_differ() {
+    assert_ne!(
+        samples(Weighting::Uniform, 1, 50),
+        samples(Weighting::Uniform, 2, 50)
+    );
+}
+
+#[test]
+fn samples_are_programs_of_the_vsa() {
+    let vsa = example_vsa();
+    let programs = VSA::ranked(&vsa).collect::<HashSet<_>>();
+    assert_eq!(programs.len(), 7);
+    let sampled = samples(Weighting::Uniform, 0, 500)
+        .into_iter()
+        .collect::<HashSet<_>>();
+    assert_eq!(sampled, programs);
+}
+
+#[test]
+fn uniform_and_cost_weighting() {
+    let count_x = |weighting| {
+        samples(weighting, 7, 7000)
+            .into_iter()
+            .filter(|ast| *ast == lit(Lit::Input(0)))
+            .count()
+    };
+    // 1 of 7 programs
+    let uniform = count_x(Weighting::Uniform);
+    assert!((800..1200).contains(&uniform), "{uniform}");
+    // X costs 1 and the concats 4, so X has weight 2^-1 against 6 * 2^-4
+    let weighted = count_x(Weighting::Cost { decay: 0.5 });
+    assert!((3500..4500).contains(&weighted), "{weighted}");
+
+    let mut counts: HashMap<AST, usize> = HashMap::new();
+    for ast in samples(Weighting::Uniform, 7, 7000) {
+        *counts.entry(ast).or_default() += 1;
+    }
+    assert!(
+        counts.values().all(|n| (800..1200).contains(n)),
+        "{counts:?}"
+    );
+}
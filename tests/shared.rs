@@ -0,0 +1,124 @@
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+
+use interactive_vsa::synth::vsa;
+use interactive_vsa::synth::witness::{ConcatSplitWitness, ConstWitness};
+use interactive_vsa::{
+    learn_with, Bank, Budget, Cost, Fun, InputLit, Language, Lit, StringDsl, SynthConfig, Witness,
+    WitnessCtx, WitnessRegistry, VSA,
+};
+
+fn s(s: &str) -> Lit {
+    Lit::StringConst(s.to_string())
+}
+
+// goal = goal.lower(), which only leads back to the goal itself
+struct Loop;
+
+impl Witness<StringDsl> for Loop {
+    fn name(&self) -> &'static str {
+        "loop"
+    }
+
+    fn witness(&self, ctx: &WitnessCtx<StringDsl>, goal: &Lit) -> Vec<VSA> {
+        vec![VSA::Join {
+            op: Fun::Lowercase,
+            children: vec![ctx.hole(goal.clone())],
+            children_goals: vec![goal.clone()],
+        }]
+    }
+}
+
+// Every split of the goal has both halves as subgoals, so the same goals come
+// up over and over. Each is only expanded once and its node shared, and the
+// cycles through `Loop` are dropped.
+#[test]
+fn learner_shares_goals_and_drops_cycles() {
+    let config = SynthConfig::<StringDsl> {
+        witnesses: WitnessRegistry::new()
+            .with(ConstWitness)
+            .with(ConcatSplitWitness)
+            .with(Loop),
+        ..SynthConfig::default()
+    };
+    let goal = s(&"a".repeat(12));
+    let vsa = learn_with::<StringDsl>(
+        &s("b"),
+        &goal,
+        &Default::default(),
+        &Bank::new(),
+        &config,
+        usize::MAX,
+        &Budget::unlimited(),
+    );
+
+    let stats = vsa.stats();
+    assert!(stats.nodes < 100, "{stats}");
+    assert!(stats.programs > 100_000, "{stats}");
+    for ast in VSA::ranked(&vsa).take(100) {
+        assert_eq!(ast.eval(&s("b")), goal, "{ast}");
+        assert!(!ast.to_string().contains("lower"), "{ast}");
+    }
+}
+
+static COSTS: AtomicUsize = AtomicUsize::new(0);
+
+#[derive(PartialEq, Eq, Hash, Clone, Debug)]
+struct Counted(usize);
+
+impl InputLit for Counted {
+    fn is_input(&self) -> bool {
+        false
+    }
+
+    fn input_value(&self, _inp: &Self) -> Self {
+        self.clone()
+    }
+}
+
+// counts how often a program's cost is asked for
+impl Cost for Counted {
+    fn cost(&self) -> usize {
+        COSTS.fetch_add(1, Ordering::Relaxed);
+        self.0
+    }
+}
+
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+struct Id;
+
+impl Language<Counted> for Id {
+    fn eval(&self, args: &[Counted], _input: &Counted) -> Counted {
+        args[0].clone()
+    }
+}
+
+impl Cost for Id {
+    fn cost(&self) -> usize {
+        1
+    }
+}
+
+// 64 diamonds on top of each other, each a union with the one below as both
+// of its alternatives. There are 2^64 paths down to the leaf, but only 65
+// nodes to solve.
+#[test]
+fn pick_best_solves_shared_nodes_once() {
+    let leaf = vsa::VSA::<Counted, Id>::Leaf(
+        [3, 1, 2]
+            .into_iter()
+            .map(|n| Arc::new(vsa::AST::Lit(Counted(n))))
+            .collect(),
+    );
+    let mut top = Arc::new(leaf);
+    for _ in 0..64 {
+        top = Arc::new(vsa::VSA::Union(vec![top.clone(), top]));
+    }
+
+    let (cost, ast) = top.pick_best().unwrap();
+    assert_eq!((cost, ast), (1, vsa::AST::Lit(Counted(1))));
+    assert_eq!(COSTS.load(Ordering::Relaxed), 3);
+    assert_eq!(top.stats().nodes, 65);
+}
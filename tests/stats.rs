@@ -0,0 +1,59 @@
+use std::sync::Arc;
+
+use interactive_vsa::{Fun, Lit, AST, VSA};
+
+fn s(s: &str) -> Lit {
+    Lit::StringConst(s.to_string())
+}
+
+fn leaf(lits: impl IntoIterator<Item = Lit>) -> Arc<VSA> {
+    Arc::new(VSA::Leaf(
+        lits.into_iter()
+            .map(|lit| Arc::new(AST::Lit(lit)))
+            .collect(),
+    ))
+}
+
+fn concat(lhs: Arc<VSA>, rhs: Arc<VSA>) -> Arc<VSA> {
+    Arc::new(VSA::Join {
+        op: Fun::Concat,
+        children: vec![lhs, rhs],
+        children_goals: vec![s(""), s("")],
+    })
+}
+
+// (X | "") <> " ", or X | "" again through the same node
+#[test]
+fn shared_nodes_are_counted_once() {
+    let words = leaf([Lit::Input(0), s("")]);
+    let join = concat(words.clone(), leaf([s(" ")]));
+    let vsa = VSA::Union(vec![join, words]);
+
+    let stats = vsa.stats();
+    assert_eq!(vsa.count_programs(), 4);
+    assert_eq!(
+        (stats.programs, stats.nodes, stats.edges, stats.depth),
+        (4, 4, 4, 3)
+    );
+    assert_eq!(stats.to_string(), "4 programs (4 nodes, 4 edges, depth 3)");
+}
+
+// every join squares the count, 2^128 programs just don't fit in a u128
+#[test]
+fn program_counts_saturate() {
+    let mut vsa = leaf([Lit::Input(0), s("")]);
+    for _ in 0..7 {
+        vsa = concat(vsa.clone(), vsa);
+    }
+
+    let stats = vsa.stats();
+    assert_eq!(stats.programs, u128::MAX);
+    assert_eq!((stats.nodes, stats.edges, stats.depth), (8, 14, 8));
+    assert!(stats.to_string().starts_with("at least "), "{stats}");
+
+    // one join less is still exact
+    let VSA::Join { children, .. } = vsa.as_ref() else {
+        unreachable!()
+    };
+    assert_eq!(children[0].count_programs(), 1 << 64);
+}
@@ -0,0 +1,103 @@
+use interactive_vsa::{Lit, Task, TaskError};
+
+fn s(s: &str) -> Lit {
+    Lit::StringConst(s.to_string())
+}
+
+#[test]
+fn json_examples_and_other_inputs() {
+    let task = Task::from_json(
+        r#"{
+            "examples": [
+                { "input": "First Last", "output": "F.L." },
+                { "input": "abc", "output": { "loc": 2 } },
+                { "input": "abc", "output": true }
+            ],
+            "other_inputs": ["Another Name"],
+            "settings": { "learn_depth": 3 }
+        }"#,
+    )
+    .unwrap();
+    assert_eq!(
+        task.examples,
+        vec![
+            (s("First Last"), s("F.L.")),
+            (s("abc"), Lit::LocConst(2)),
+            (s("abc"), Lit::BoolConst(true)),
+        ]
+    );
+    assert_eq!(task.other_inputs, vec![s("Another Name")]);
+    assert_eq!(task.settings.learn_depth, 3);
+}
+
+#[test]
+fn json_without_examples_is_an_error() {
+    assert!(matches!(
+        Task::from_json(r#"{ "examples": [] }"#),
+        Err(TaskError::Format(_))
+    ));
+    assert!(matches!(
+        Task::from_json(r#"{ "examples": "#),
+        Err(TaskError::Json(_))
+    ));
+}
+
+#[test]
+fn json_settings_moved_to_the_config_are_an_error() {
+    let err = Task::from_json(
+        r#"{
+            "examples": [{ "input": "a", "output": "a" }],
+            "settings": { "max_size": 6 }
+        }"#,
+    )
+    .unwrap_err();
+    assert!(err.to_string().contains("max_size"), "{err}");
+}
+
+#[test]
+fn csv_with_header() {
+    let task = Task::from_csv("input,output\nFirst Last,F.L.\nAda Lovelace,\n").unwrap();
+    assert_eq!(task.examples, vec![(s("First Last"), s("F.L."))]);
+    assert_eq!(task.other_inputs, vec![s("Ada Lovelace")]);
+}
+
+#[test]
+fn csv_without_header() {
+    let task = Task::from_csv("a b,a\nc d,c\n").unwrap();
+    assert_eq!(task.examples, vec![(s("a b"), s("a")), (s("c d"), s("c"))]);
+    assert!(task.other_inputs.is_empty());
+}
+
+#[test]
+fn csv_typed_outputs() {
+    let task = Task::from_csv("input,output:bool\nabc,true\nab,false\n").unwrap();
+    assert_eq!(
+        task.examples,
+        vec![
+            (s("abc"), Lit::BoolConst(true)),
+            (s("ab"), Lit::BoolConst(false)),
+        ]
+    );
+
+    let err = Task::from_csv("input,output:loc\nabc,two\n").unwrap_err();
+    assert!(matches!(err, TaskError::Format(_)), "{err}");
+}
+
+#[test]
+fn csv_rows_must_have_the_same_width() {
+    let err = Task::from_csv("a,b,c\nd,e\n").unwrap_err();
+    assert!(matches!(err, TaskError::Format(_)), "{err}");
+}
+
+#[test]
+fn loads_by_extension() {
+    let dir = env!("CARGO_MANIFEST_DIR");
+    let csv = Task::load(format!("{dir}/tasks/names.csv")).unwrap();
+    assert_eq!(csv.examples, vec![(s("First Last"), s("F.L."))]);
+    assert_eq!(csv.other_inputs, vec![s("Another Name")]);
+
+    let json = Task::load(format!("{dir}/tasks/cookies.json")).unwrap();
+    assert_eq!(json.examples.len(), 3);
+    assert_eq!(json.examples[0], (s("I have 17 cookies"), s("17")));
+    assert_eq!(json.config.max_size, 6);
+}
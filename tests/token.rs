@@ -0,0 +1,56 @@
+use interactive_vsa::{Fun, Language, Lit, StringDsl, SynthConfig, TaskError, Token};
+
+fn s(s: &str) -> Lit {
+    Lit::StringConst(s.to_string())
+}
+
+fn find(inp: &str, token: Token) -> Lit {
+    let inp = s(inp);
+    Fun::Find.eval(&[inp.clone(), Lit::Token(token), Lit::LocConst(0)], &inp)
+}
+
+fn config(json: &str) -> Result<SynthConfig<StringDsl>, TaskError> {
+    SynthConfig::from_json(json)
+}
+
+#[test]
+fn literals_only_match_themselves() {
+    let dot = || Token::Literal(".".to_string());
+    assert_eq!(find("ab.c", dot()), Lit::LocConst(2));
+    assert_eq!(find("abc", dot()), Lit::LocEnd);
+    let star = || Token::Literal("a*".to_string());
+    assert_eq!(find("ba*", star()), Lit::LocConst(1));
+    assert_eq!(find("b", star()), Lit::LocEnd);
+}
+
+#[test]
+fn regexes_match_as_a_group() {
+    let ab = Token::regex("a|b").unwrap();
+    assert_eq!(ab.to_string(), "/a|b/");
+    let ab_dot = ab.then(&Token::Literal(".".to_string())).unwrap();
+    assert_eq!(find("xa.", ab_dot.clone()), Lit::LocConst(1));
+    // without the group this would be `a|b\.` and match the lone a
+    assert_eq!(find("a b.", ab_dot), Lit::LocConst(2));
+}
+
+#[test]
+fn tokens_too_big_together_are_an_error() {
+    let words = Token::regex(r"\w{200}").unwrap();
+    assert!(words.then(&words).is_err());
+    assert!(words.then(&Token::Literal(".".to_string())).is_ok());
+}
+
+#[test]
+fn invalid_tokens_in_a_config_are_an_error() {
+    assert!(Token::regex("[a-").is_err());
+    let err = config(r#"{ "tokens": [{ "regex": "[a-" }] }"#).unwrap_err();
+    assert!(matches!(err, TaskError::Format(_)), "{err}");
+    let err = config(r#"{ "tokens": ["digit"] }"#).unwrap_err();
+    assert!(matches!(err, TaskError::Format(_)), "{err}");
+
+    let config =
+        config(r#"{ "tokens": ["digits", { "literal": "." }, { "regex": "[a-z]+" }] }"#).unwrap();
+    assert!(config
+        .tokens
+        .contains(&Lit::Token(Token::regex("[a-z]+").unwrap())));
+}
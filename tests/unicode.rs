@@ -0,0 +1,143 @@
+use interactive_vsa::{top_down, Fun, Language, Lit, Task, Token, AST};
+
+fn s(s: &str) -> Lit {
+    Lit::StringConst(s.to_string())
+}
+
+fn literal(s: &str) -> Lit {
+    Lit::Token(Token::Literal(s.to_string()))
+}
+
+fn app(fun: Fun, args: Vec<AST>) -> AST {
+    AST::App { fun, args }
+}
+
+fn lit(l: Lit) -> AST {
+    AST::Lit(l)
+}
+
+// X[start..end]
+fn slice(start: AST, end: AST) -> AST {
+    app(Fun::Slice, vec![start, end])
+}
+
+// X.find(pattern, 0)
+fn find(pattern: &str) -> AST {
+    app(
+        Fun::Find,
+        vec![lit(Lit::Input(0)), lit(literal(pattern)), lit(Lit::LocConst(0))],
+    )
+}
+
+fn learned(examples: &[(&str, &str)]) -> AST {
+    let examples = examples
+        .iter()
+        .map(|(inp, out)| (s(inp), s(out)))
+        .collect::<Vec<_>>();
+    let (_, ast) = top_down(&examples);
+    let ast = ast.expect("no program found");
+    for (inp, out) in &examples {
+        assert_eq!(ast.eval(inp), *out, "{ast} on {inp}");
+    }
+    ast
+}
+
+#[test]
+fn find_counts_chars() {
+    let inp = s("héllo wörld");
+    assert_eq!(find(" ").eval(&inp), Lit::LocConst(5));
+    assert_eq!(find("w").eval(&inp), Lit::LocConst(6));
+    assert_eq!(find("ö").eval(&inp), Lit::LocConst(7));
+    assert_eq!(
+        Fun::FindEnd.eval(&[inp.clone(), literal("ö"), Lit::LocConst(0)], &inp),
+        Lit::LocConst(8)
+    );
+    assert_eq!(find("東").eval(&s("東京")), Lit::LocConst(0));
+    assert_eq!(find("x").eval(&inp), Lit::LocEnd);
+}
+
+#[test]
+fn slice_counts_chars() {
+    let prog = slice(lit(Lit::LocConst(0)), find(" "));
+    assert_eq!(prog.eval(&s("héllo wörld")), s("héllo"));
+    assert_eq!(prog.eval(&s("日本語 テキスト")), s("日本語"));
+    assert_eq!(prog.eval(&s("👋🏽 hi")), s("👋🏽"));
+
+    let rest = slice(lit(Lit::LocConst(1)), lit(Lit::LocEnd));
+    assert_eq!(rest.eval(&s("東京都")), s("京都"));
+    assert_eq!(rest.eval(&s("é")), s(""));
+}
+
+#[test]
+fn slice_out_of_bounds_is_empty() {
+    let inp = s("ñ");
+    for (start, end) in [(0, 2), (2, 3), (1, 0)] {
+        let prog = slice(lit(Lit::LocConst(start)), lit(Lit::LocConst(end)));
+        assert_eq!(prog.eval(&inp), s(""));
+    }
+    let prog = slice(lit(Lit::LocConst(2)), lit(Lit::LocEnd));
+    assert_eq!(prog.eval(&inp), s(""));
+}
+
+#[test]
+fn end_of_input_counts_chars() {
+    let inp = s("añb");
+    assert_eq!(
+        Fun::Equal.eval(&[Lit::LocConst(3), Lit::LocEnd], &inp),
+        Lit::BoolConst(true)
+    );
+    assert_eq!(
+        Fun::Equal.eval(&[Lit::LocConst(4), Lit::LocEnd], &inp),
+        Lit::BoolConst(false)
+    );
+
+    // the empty pattern matches before every char and at the end, so the
+    // match at index $ is the end
+    for fun in [Fun::Find, Fun::FindEnd] {
+        assert_eq!(
+            fun.eval(&[inp.clone(), literal(""), Lit::LocEnd], &inp),
+            Lit::LocConst(3)
+        );
+    }
+}
+
+#[test]
+fn learns_first_word_with_accents() {
+    let ast = learned(&[("Zoë Saldaña", "Zoë"), ("Jürgen Klopp", "Jürgen")]);
+    assert_eq!(ast.eval(&s("Éowyn of Rohan")), s("Éowyn"));
+}
+
+#[test]
+fn learns_last_word_in_japanese() {
+    let ast = learned(&[("東京 タワー", "タワー"), ("大阪 城", "城")]);
+    assert_eq!(ast.eval(&s("京都 タワー")), s("タワー"));
+}
+
+#[test]
+fn learns_around_emoji() {
+    let ast = learned(&[("🍕 pizza", "pizza"), ("🍣 sushi", "sushi")]);
+    assert_eq!(ast.eval(&s("🌮 tacos")), s("tacos"));
+}
+
+#[test]
+fn learns_initials_in_cyrillic() {
+    let ast = learned(&[("Анна Каренина", "А.К."), ("Лев Толстой", "Л.Т.")]);
+    assert_eq!(ast.eval(&s("Фёдор Достоевский")), s("Ф.Д."));
+}
+
+#[test]
+fn learns_uppercase_with_accents() {
+    let ast = learned(&[("café au lait", "CAFÉ"), ("crème brûlée", "CRÈME")]);
+    assert_eq!(ast.eval(&s("déjà vu")), s("DÉJÀ"));
+}
+
+#[test]
+fn location_outputs_count_chars() {
+    let task = Task::from_csv("input,output:loc\nñu mar,2\nárbol río,5\n").unwrap();
+    let (_, ast) = top_down(&task.examples);
+    let ast = ast.expect("no program found");
+    for (inp, out) in &task.examples {
+        assert_eq!(ast.eval(inp), *out, "{ast} on {inp}");
+    }
+    assert_eq!(ast.eval(&s("águila real")), Lit::LocConst(6));
+}